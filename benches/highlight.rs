@@ -0,0 +1,49 @@
+// Whole-string highlighting against the line-at-a-time streaming path the CLI
+// uses, on a sheet of several thousand lines
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use py_cheat::{PythonHighlighter, BUILTIN_SHEETS};
+use std::io::{self, Write};
+
+// Counts the bytes written without keeping them, as a terminal would
+struct Sink(usize);
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn large_sheet() -> String {
+    let sheets: String = BUILTIN_SHEETS.iter().map(|(_, content)| *content).collect();
+    sheets.repeat(4)
+}
+
+fn highlight(c: &mut Criterion) {
+    let code = large_sheet();
+    let highlighter = PythonHighlighter::with_color(true);
+    let mut group = c.benchmark_group(format!("highlight {} lines", code.lines().count()));
+
+    group.bench_function("whole string", |b| {
+        b.iter_batched(
+            || Sink(0),
+            |mut sink| sink.write_all(highlighter.highlight(&code).as_bytes()),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("streamed", |b| {
+        b.iter_batched(
+            || Sink(0),
+            |mut sink| highlighter.highlight_stream(code.as_bytes(), &mut sink),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, highlight);
+criterion_main!(benches);
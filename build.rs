@@ -0,0 +1,36 @@
+// Fails the build if an embedded sheet has no sections or misnumbered ones,
+// using the same marker detection as the runtime parser
+#[path = "src/cheatsheet/markers.rs"]
+mod markers;
+
+use std::fs;
+
+const SHEETS: [&str; 3] = ["src/Basics.py", "src/Intermediate.py", "src/Advanced.py"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/cheatsheet/markers.rs");
+
+    for path in SHEETS {
+        println!("cargo:rerun-if-changed={}", path);
+        let content =
+            fs::read_to_string(path).unwrap_or_else(|e| panic!("Could not read {}: {}", path, e));
+        let lines = markers::split_lines(&content);
+        let section_starts = markers::find_section_starts(&lines);
+
+        if section_starts.is_empty() {
+            panic!("{} has no `# ----` / `# N. Title` sections", path);
+        }
+        let mismatches = markers::numbering_mismatches(&lines, &section_starts);
+        if !mismatches.is_empty() {
+            let details: Vec<String> = mismatches
+                .iter()
+                .map(|(position, number)| format!("section {} is numbered {}", position, number))
+                .collect();
+            panic!(
+                "{} has out-of-sequence section numbers: {}",
+                path,
+                details.join(", ")
+            );
+        }
+    }
+}
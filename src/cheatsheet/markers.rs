@@ -0,0 +1,49 @@
+// Section marker detection shared by the runtime parser and build.rs, which
+// includes this file directly to check the embedded sheets at compile time.
+// It must therefore depend on nothing but std.
+
+// `lines` already splits on CRLF, but a stray `\r` (e.g. before EOF) would
+// otherwise leak into titles and content
+pub fn lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+pub fn split_lines(content: &str) -> Vec<&str> {
+    lines(content).collect()
+}
+
+// The number and title of a `# N. Title` line
+pub fn title_parts(line: &str) -> Option<(&str, &str)> {
+    line.trim_start_matches("# ").split_once(". ")
+}
+
+// Whether `line` is a `# ----` line that opens a section, i.e. is directly
+// followed by a `# N. Title` line
+pub fn opens_section(line: &str, next: &str) -> bool {
+    line.starts_with("# ----") && next.starts_with("# ") && title_parts(next).is_some()
+}
+
+// Indices of the `# ----` lines that open a section
+pub fn find_section_starts(lines: &[&str]) -> Vec<usize> {
+    lines
+        .windows(2)
+        .enumerate()
+        .filter_map(|(i, window)| opens_section(window[0], window[1]).then_some(i))
+        .collect()
+}
+
+// (position, number written in the file) for each section whose `# N. Title`
+// number doesn't match its position
+pub fn numbering_mismatches(lines: &[&str], section_starts: &[usize]) -> Vec<(usize, String)> {
+    section_starts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &start_idx)| {
+            let (number, _) = title_parts(lines[start_idx + 1])?;
+            let expected = i + 1;
+            (number.trim().parse::<usize>() != Ok(expected)).then(|| (expected, number.to_string()))
+        })
+        .collect()
+}
@@ -1,5 +1,6 @@
+mod markers;
 pub mod parser;
 pub mod section;
 
-pub use parser::CheatSheet;
-pub use section::Section;
+pub use parser::{CheatSheet, ParseOptions};
+pub use section::{Fence, Section};
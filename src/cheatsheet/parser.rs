@@ -1,73 +1,266 @@
+use super::markers;
 use super::section::Section;
-use std::error::Error;
+use crate::error::CheatError;
+use schemars::JsonSchema;
+use serde::Serialize;
 
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct CheatSheet {
     pub sections: Vec<Section>,
 }
 
+// How section headers are recognized. The defaults are the built-in sheets'
+// convention: a `# ----` rule, `# N. Title` and another rule. Any other
+// marker or separator means one-line headers instead, such as `## 3: Title`
+// for a marker of `## ` and a separator of `:`, whose number must be a
+// number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub marker: String,
+    pub title_separator: String,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            marker: "# ".to_string(),
+            title_separator: ". ".to_string(),
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Indices of the lines that open a section
+    fn section_starts(&self, lines: &[&str]) -> Vec<usize> {
+        if self.is_default() {
+            return markers::find_section_starts(lines);
+        }
+        (0..lines.len())
+            .filter(|&i| self.header_parts(lines[i]).is_some())
+            .collect()
+    }
+
+    // Whether `line`, followed by `next`, opens a section
+    fn opens_section(&self, line: &str, next: Option<&str>) -> bool {
+        if self.is_default() {
+            return next.is_some_and(|next| markers::opens_section(line, next));
+        }
+        self.header_parts(line).is_some()
+    }
+
+    // Lines of framing a section opens with: rule, title and rule, or just
+    // the header
+    fn framing_lines(&self) -> usize {
+        if self.is_default() {
+            3
+        } else {
+            1
+        }
+    }
+
+    // The number and title of a one-line header
+    fn header_parts<'a>(&self, line: &'a str) -> Option<(&'a str, &'a str)> {
+        let (number, title) = line
+            .strip_prefix(&self.marker)?
+            .split_once(&self.title_separator)?;
+        number.trim().parse::<u32>().ok()?;
+        Some((number, title))
+    }
+
+    // The number and title of the section opening at `start`
+    fn title_parts<'a>(&self, lines: &[&'a str], start: usize) -> Option<(&'a str, &'a str)> {
+        if self.is_default() {
+            return markers::title_parts(lines.get(start + 1)?);
+        }
+        self.header_parts(lines[start])
+    }
+}
+
 impl CheatSheet {
-    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
-        let lines: Vec<&str> = content.lines().collect();
-        let section_starts = Self::find_section_starts(&lines);
-        let sections = Self::build_sections(&lines, &section_starts)?;
+    pub fn parse(content: &str) -> Result<Self, CheatError> {
+        Self::parse_with(content, &ParseOptions::default())
+    }
+
+    pub fn parse_with(content: &str, options: &ParseOptions) -> Result<Self, CheatError> {
+        let lines = markers::split_lines(content);
+        let section_starts = options.section_starts(&lines);
+        let sections = Self::build_sections(&lines, &section_starts, options)?;
 
         Ok(CheatSheet { sections })
     }
 
-    fn find_section_starts(lines: &[&str]) -> Vec<usize> {
-        lines
-            .windows(2)
-            .enumerate()
-            .filter_map(|(i, window)| {
-                if window[0].starts_with("# ----")
-                    && window[1].starts_with("# ")
-                    && window[1]
-                        .trim_start_matches("# ")
-                        .split_once(". ")
-                        .is_some()
-                {
-                    Some(i)
-                } else {
-                    None
+    // Just the section at zero-based `index`, or None when there are fewer
+    // sections. Lines are only scanned up to the next section's header and no
+    // other section is built, so showing one section of a big sheet doesn't
+    // parse all of it.
+    pub fn parse_nth(content: &str, index: usize) -> Result<Option<Section>, CheatError> {
+        Self::parse_nth_with(content, index, &ParseOptions::default())
+    }
+
+    pub fn parse_nth_with(
+        content: &str,
+        index: usize,
+        options: &ParseOptions,
+    ) -> Result<Option<Section>, CheatError> {
+        // Split line by line as `split_lines` splits them, only as far as needed
+        let mut lines = markers::lines(content).peekable();
+        let mut section_lines = Vec::new();
+        let mut seen = 0;
+        while let Some(line) = lines.next() {
+            if options.opens_section(line, lines.peek().copied()) {
+                if !section_lines.is_empty() {
+                    break;
                 }
-            })
-            .collect()
+                seen += 1;
+            }
+            if seen == index + 1 {
+                section_lines.push(line);
+            }
+        }
+
+        if section_lines.is_empty() {
+            return Ok(None);
+        }
+        let end = section_lines.len();
+        Self::create_section(&section_lines, index + 1, 0, end, options).map(Some)
+    }
+
+    // Checks that the `# N. Title` numbers written in the file run 1, 2, 3...
+    // so they agree with the positional numbers used on the command line
+    pub fn validate_numbering(content: &str) -> Result<(), CheatError> {
+        Self::validate_numbering_with(content, &ParseOptions::default())
+    }
+
+    pub fn validate_numbering_with(
+        content: &str,
+        options: &ParseOptions,
+    ) -> Result<(), CheatError> {
+        let lines = markers::split_lines(content);
+        let section_starts = options.section_starts(&lines);
+        let mismatches = if options.is_default() {
+            markers::numbering_mismatches(&lines, &section_starts)
+        } else {
+            section_starts
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &start)| {
+                    let (number, _) = options.title_parts(&lines, start)?;
+                    (number.trim().parse::<usize>() != Ok(i + 1))
+                        .then(|| (i + 1, number.to_string()))
+                })
+                .collect()
+        };
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(CheatError::SectionNumbering(mismatches))
+        }
     }
 
     fn build_sections(
         lines: &[&str],
         section_starts: &[usize],
-    ) -> Result<Vec<Section>, Box<dyn Error>> {
+        options: &ParseOptions,
+    ) -> Result<Vec<Section>, CheatError> {
         section_starts
             .iter()
             .enumerate()
             .map(|(i, &start_idx)| {
                 let end_idx = section_starts.get(i + 1).copied().unwrap_or(lines.len());
-                Self::create_section(lines, start_idx, end_idx)
+                Self::create_section(lines, i + 1, start_idx, end_idx, options)
             })
             .collect()
     }
 
     fn create_section(
         lines: &[&str],
+        position: usize,
         start_idx: usize,
         end_idx: usize,
-    ) -> Result<Section, Box<dyn Error>> {
-        let title_line = lines.get(start_idx + 1).ok_or("Missing section title")?;
-
-        let (_, section_title) = title_line
-            .trim_start_matches("# ")
-            .split_once(". ")
-            .ok_or("Invalid section title format")?;
-
-        let section_content = format!(
-            "{}\n{}\n{}\n{}",
-            lines[start_idx],
-            title_line,
-            lines[start_idx + 2],
-            lines[start_idx + 3..end_idx].join("\n")
-        );
-
-        Ok(Section::new(section_title.to_string(), section_content))
+        options: &ParseOptions,
+    ) -> Result<Section, CheatError> {
+        let (number, section_title) = options
+            .title_parts(lines, start_idx)
+            .ok_or_else(|| CheatError::ParseFailed("Invalid section title format".into()))?;
+
+        // The framing (rule, title and closing rule by default), then the
+        // body. A section cut short by the end of the file (or the next
+        // section) keeps whatever exists.
+        let framing_end = (start_idx + options.framing_lines()).min(end_idx);
+        let framing = lines.get(start_idx..framing_end).unwrap_or_default();
+        let body = lines.get(framing_end..end_idx).unwrap_or_default();
+        let section_content = format!("{}\n{}", framing.join("\n"), body.join("\n"));
+
+        // A number that isn't one (validate_numbering reports it) falls back
+        // to the position
+        let number = number.trim().parse().unwrap_or(position as u32);
+        let section = Section::new(number, normalize_title(section_title), section_content);
+        Ok(match options.is_default() {
+            true => section,
+            false => section.with_framing(framing.len()),
+        })
+    }
+}
+
+// A title as authored, trimmed and with runs of whitespace collapsed to one
+// space, so stray spacing doesn't show in listings or affect title matching.
+// The content keeps the title line exactly as written.
+fn normalize_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crlf_sheet_keeps_no_carriage_returns() {
+        let content = "# -----\r\n# 1. First\r\n# -----\r\nx = 1\r\n\r\n# -----\r\n# 2. Second\r\n# -----\r\ny = 2\r\n";
+        let sheet = CheatSheet::parse(content).unwrap();
+
+        let titles: Vec<&str> = sheet.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["First", "Second"]);
+        assert_eq!(sheet.sections[0].body(), "x = 1\n");
+        assert_eq!(sheet.sections[1].body(), "y = 2");
+        assert!(sheet.sections.iter().all(|s| !s.content.contains('\r')));
+    }
+
+    #[test]
+    fn sheet_ending_after_a_title_line_has_an_empty_body() {
+        for content in ["# -----\n# 1. Last", "# -----\n# 1. Last\n# -----"] {
+            let sheet = CheatSheet::parse(content).unwrap();
+            assert_eq!(sheet.sections.len(), 1);
+            assert_eq!(sheet.sections[0].title, "Last");
+            assert_eq!(sheet.sections[0].body(), "");
+        }
+    }
+
+    #[test]
+    fn title_with_trailing_spaces_is_trimmed() {
+        let content = "# -----\n# 1. Lists   and  tuples   \n# -----\nx = [1]\n";
+        let sheet = CheatSheet::parse(content).unwrap();
+        assert_eq!(sheet.sections[0].title, "Lists and tuples");
+        assert!(sheet.sections[0]
+            .content
+            .contains("# 1. Lists   and  tuples   \n"));
+    }
+
+    #[test]
+    fn parse_nth_matches_parse() {
+        let content = "intro\r\n# -----\r\n# 1. First\r\n# -----\r\nx = 1\r\n\r\n# -----\r\n# 2. Second\r\n# -----\r\ny = 2\r\n";
+        let sheet = CheatSheet::parse(content).unwrap();
+        for (i, section) in sheet.sections.iter().enumerate() {
+            let nth = CheatSheet::parse_nth(content, i).unwrap().unwrap();
+            assert_eq!(
+                (nth.number, &nth.title, &nth.content),
+                (section.number, &section.title, &section.content)
+            );
+        }
+        assert!(CheatSheet::parse_nth(content, 2).unwrap().is_none());
     }
 }
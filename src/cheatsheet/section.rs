@@ -1,11 +1,480 @@
-#[derive(Debug)]
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::ops::Range;
+
+#[derive(Clone, Debug, Serialize, JsonSchema)]
 pub struct Section {
+    // The `N.` written in the title line, which may differ from the section's
+    // position if the file skips or repeats numbers
+    pub number: u32,
     pub title: String,
     pub content: String,
+    // Regions fenced off as another language, which aren't highlighted as Python
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fences: Vec<Fence>,
+    // How many lines of framing the content opens with, when the sheet was
+    // parsed with other than the default markers
+    #[serde(skip)]
+    framing: Option<usize>,
+}
+
+// A `# ```lang` ... `# ```` region; `lines` indexes lines of the section
+// content and covers both delimiters. An unclosed fence runs to the end.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct Fence {
+    pub language: String,
+    pub lines: Range<usize>,
 }
 
 impl Section {
-    pub fn new(title: String, content: String) -> Self {
-        Self { title, content }
+    pub fn new(number: u32, title: String, content: String) -> Self {
+        let fences = find_fences(&content);
+        Self {
+            number,
+            title,
+            content,
+            fences,
+            framing: None,
+        }
+    }
+
+    // Marks the first `lines` lines of the content as its framing, for
+    // headers `body` wouldn't recognize
+    pub(crate) fn with_framing(mut self, lines: usize) -> Self {
+        self.framing = Some(lines);
+        self
+    }
+
+    fn in_fence(&self, line: usize) -> bool {
+        self.fences.iter().any(|fence| fence.lines.contains(&line))
+    }
+
+    // Content with the leading `# ----` rule and `# N. Title` framing removed
+    pub fn body(&self) -> &str {
+        if let Some(lines) = self.framing {
+            return self
+                .content
+                .splitn(lines + 1, '\n')
+                .nth(lines)
+                .unwrap_or_default();
+        }
+        let mut rest = self.content.as_str();
+        for _ in 0..3 {
+            let (line, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+            let is_framing = line.starts_with("# ----")
+                || (line.starts_with("# ") && line.split_once(". ").is_some());
+            if !is_framing {
+                break;
+            }
+            rest = tail;
+        }
+        rest
+    }
+
+    // Content cut down to runnable Python: framing, fenced regions and
+    // unindented prose comments are dropped, along with the blank runs they
+    // leave behind. Indented and inline comments stay, as does everything
+    // inside a triple-quoted string.
+    pub fn code(&self) -> String {
+        let mut lines: Vec<&str> = Vec::new();
+        let mut open_string = None;
+        let framing = self.content.lines().count() - self.body().lines().count();
+
+        for (i, line) in self.body().lines().enumerate() {
+            if self.in_fence(framing + i) {
+                continue;
+            }
+            let in_string = open_string.is_some();
+            open_string = open_triple_quote(line, open_string);
+            if in_string {
+                lines.push(line);
+                continue;
+            }
+
+            let is_blank = line.trim().is_empty();
+            let after_blank = lines.last().is_none_or(|last| last.trim().is_empty());
+            if line.starts_with('#') || (is_blank && after_blank) {
+                continue;
+            }
+            lines.push(line);
+        }
+
+        while lines.last().is_some_and(|last| last.trim().is_empty()) {
+            lines.pop();
+        }
+        let mut code = lines.join("\n");
+        code.push('\n');
+        code
+    }
+
+    // Non-blank lines of the body that aren't comments, including lines that
+    // end in an inline comment and everything inside a triple-quoted string
+    pub fn code_lines(&self) -> impl Iterator<Item = &str> {
+        self.classified_lines()
+            .filter(|(_, is_comment)| !is_comment)
+            .map(|(line, _)| line)
+    }
+
+    // Lines of the body whose first non-blank character is `#`, outside any
+    // triple-quoted string. The framing isn't part of the body.
+    pub fn comment_lines(&self) -> impl Iterator<Item = &str> {
+        self.classified_lines()
+            .filter(|(_, is_comment)| *is_comment)
+            .map(|(line, _)| line)
+    }
+
+    // The body's first prose comment without its `#`, else its first code
+    // line, trimmed
+    pub fn first_meaningful_line(&self) -> Option<&str> {
+        match self.comment_lines().next() {
+            Some(comment) => Some(comment.trim().trim_start_matches('#').trim_start()),
+            None => self.code_lines().next().map(str::trim),
+        }
+    }
+
+    // Each non-blank body line, and whether it's a comment
+    fn classified_lines(&self) -> impl Iterator<Item = (&str, bool)> {
+        let mut open_string = None;
+        self.body().lines().filter_map(move |line| {
+            let in_string = open_string.is_some();
+            open_string = open_triple_quote(line, open_string);
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some((line, !in_string && trimmed.starts_with('#')))
+            }
+        })
+    }
+
+    // A copy whose content is just `code()`. Like every cut-down copy it has
+    // no framing, so its `body()` is the whole of it.
+    pub fn code_only(&self) -> Section {
+        Section::new(self.number, self.title.clone(), self.code()).with_framing(0)
+    }
+
+    // A copy cut down to `code_lines()`, one after another with no blank
+    // lines between them, and without their inline comments unless
+    // `inline_comments` is set
+    pub fn minified(&self, inline_comments: bool) -> Section {
+        let mut content = String::new();
+        let mut open_string = None;
+        for line in self.code_lines() {
+            let start = match inline_comments {
+                true => None,
+                false => comment_start(line, open_string),
+            };
+            open_string = open_triple_quote(line, open_string);
+            match start {
+                Some(start) => content.push_str(line[..start].trim_end()),
+                None => content.push_str(line),
+            }
+            content.push('\n');
+        }
+        Section::new(self.number, self.title.clone(), content).with_framing(0)
+    }
+
+    // A copy laid out as explanations and the code they explain: each run of
+    // comment lines, its last one ending in a `↴` connector, then the code
+    // lines after it dedented together and indented under it. Code before the
+    // first comment stands alone. The connector stays in the comment, so the
+    // code still highlights as Python.
+    pub fn explained(&self) -> Section {
+        let mut pairs: Vec<(Vec<&str>, Vec<&str>)> = Vec::new();
+        for (line, is_comment) in self.classified_lines() {
+            match pairs.last_mut() {
+                Some((comments, code)) if is_comment && code.is_empty() => comments.push(line),
+                Some((_, code)) if !is_comment => code.push(line),
+                _ if is_comment => pairs.push((vec![line], Vec::new())),
+                _ => pairs.push((Vec::new(), vec![line])),
+            }
+        }
+
+        let mut content = String::new();
+        for (i, (comments, code)) in pairs.iter().enumerate() {
+            if i > 0 {
+                content.push('\n');
+            }
+            for (j, comment) in comments.iter().enumerate() {
+                content.push_str(comment.trim());
+                if j + 1 == comments.len() && !code.is_empty() {
+                    content.push_str(" ↴");
+                }
+                content.push('\n');
+            }
+            let hang = if comments.is_empty() { "" } else { "   " };
+            let indent = code.iter().map(|line| indentation(line)).min().unwrap_or(0);
+            for line in code {
+                content.push_str(hang);
+                content.push_str(&line[indent..]);
+                content.push('\n');
+            }
+        }
+        Section::new(self.number, self.title.clone(), content).with_framing(0)
+    }
+
+    // A copy whose content is just `body()`, comments and all
+    pub fn body_only(&self) -> Section {
+        Section::new(self.number, self.title.clone(), self.body().to_string()).with_framing(0)
+    }
+
+    // A copy introduced by a bare `N. Title` line instead of the `# ----`
+    // rules around a `# N. Title` comment
+    pub fn plainly_framed(&self) -> Section {
+        let content = format!("{}. {}\n{}", self.number, self.title, self.body());
+        Section::new(self.number, self.title.clone(), content).with_framing(1)
+    }
+
+    // A copy whose content is cut to at most `max_bytes` bytes, backing off to
+    // a character boundary, with `…` marking the cut
+    pub fn truncated(&self, max_bytes: usize) -> Section {
+        if self.content.len() <= max_bytes {
+            return self.clone();
+        }
+        let mut end = max_bytes;
+        while !self.content.is_char_boundary(end) {
+            end -= 1;
+        }
+        Section {
+            framing: self.framing,
+            ..Section::new(
+                self.number,
+                self.title.clone(),
+                format!("{}…", &self.content[..end]),
+            )
+        }
+    }
+
+    // A copy with each tab replaced by spaces up to the next multiple of
+    // `tab_width` columns
+    pub fn tabs_expanded(&self, tab_width: usize) -> Section {
+        if !self.content.contains('\t') {
+            return self.clone();
+        }
+        let mut content = String::with_capacity(self.content.len());
+        let mut column = 0;
+        for c in self.content.chars() {
+            match c {
+                '\t' => {
+                    let spaces = tab_width - column % tab_width;
+                    content.extend(std::iter::repeat_n(' ', spaces));
+                    column += spaces;
+                }
+                '\n' => {
+                    content.push(c);
+                    column = 0;
+                }
+                _ => {
+                    content.push(c);
+                    column += 1;
+                }
+            }
+        }
+        Section {
+            framing: self.framing,
+            ..Section::new(self.number, self.title.clone(), content)
+        }
+    }
+
+    // The `def name`/`async def name`/`class name` block defining `name`:
+    // its decorators, the defining line and every line indented under it
+    pub fn definition(&self, name: &str) -> Option<String> {
+        let lines: Vec<&str> = self.body().lines().collect();
+        let start = lines.iter().position(|line| defines(line, name))?;
+        let indent = indentation(lines[start]);
+
+        let mut first = start;
+        while first > 0 && {
+            let above = lines[first - 1];
+            indentation(above) == indent && above.trim_start().starts_with('@')
+        } {
+            first -= 1;
+        }
+
+        let mut end = start + 1;
+        while end < lines.len()
+            && (lines[end].trim().is_empty() || indentation(lines[end]) > indent)
+        {
+            end += 1;
+        }
+        while end > start + 1 && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+
+        let mut block = lines[first..end].join("\n");
+        block.push('\n');
+        Some(block)
+    }
+
+    // Case-insensitive check against both title and body; `needle` must already be lowercase
+    pub fn matches(&self, needle: &str) -> bool {
+        self.title.to_lowercase().contains(needle) || self.content.to_lowercase().contains(needle)
+    }
+}
+
+// Bytes of leading spaces and tabs, the only indentation Python knows, so it
+// always ends on a char boundary
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+// Whether `line` opens a function or class called `name`
+fn defines(line: &str, name: &str) -> bool {
+    let code = line.trim_start();
+    let code = code.strip_prefix("async ").unwrap_or(code);
+    ["def ", "class "].iter().any(|keyword| {
+        code.strip_prefix(keyword)
+            .and_then(|rest| rest.trim_start().strip_prefix(name))
+            .is_some_and(|rest| rest.starts_with(['(', ':', '[', ' ']))
+    })
+}
+
+// The language named by a `# ```lang` line, or "" for a bare `# ```` closing
+// one. Python fences are reported too; callers decide what to skip.
+pub(crate) fn fence_marker(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix("# ```").map(str::trim)
+}
+
+// Fences other than Python ones, which need no special treatment
+pub(crate) fn is_foreign(language: &str) -> bool {
+    !matches!(language, "" | "py" | "python")
+}
+
+fn find_fences(content: &str) -> Vec<Fence> {
+    let mut fences = Vec::new();
+    let mut open: Option<(&str, usize)> = None;
+    let mut count = 0;
+
+    for (i, line) in content.lines().enumerate() {
+        count = i + 1;
+        let Some(language) = fence_marker(line) else {
+            continue;
+        };
+        match open {
+            Some((current, start)) if language.is_empty() => {
+                fences.push(Fence {
+                    language: current.to_string(),
+                    lines: start..i + 1,
+                });
+                open = None;
+            }
+            None if is_foreign(language) => open = Some((language, i)),
+            _ => {}
+        }
+    }
+    if let Some((language, start)) = open {
+        fences.push(Fence {
+            language: language.to_string(),
+            lines: start..count,
+        });
+    }
+
+    fences
+}
+
+// Scans one line and returns the triple-quote delimiter still open after it,
+// given the one open before it. Ordinary strings and `#` comments are skipped
+// so quotes inside them aren't mistaken for docstring delimiters.
+fn open_triple_quote(line: &str, open: Option<&'static str>) -> Option<&'static str> {
+    scan_quotes(line, open).0
+}
+
+// Byte offset of the `#` starting a comment on the line, if it has one
+// outside any string, given the triple-quote delimiter open before it
+fn comment_start(line: &str, open: Option<&'static str>) -> Option<usize> {
+    scan_quotes(line, open).1
+}
+
+// The triple-quote delimiter open after the line and where a comment on it
+// starts, as for `open_triple_quote` and `comment_start`
+fn scan_quotes(
+    line: &str,
+    mut open: Option<&'static str>,
+) -> (Option<&'static str>, Option<usize>) {
+    let mut rest = line;
+
+    'scan: while let Some(c) = rest.chars().next() {
+        if let Some(delimiter) = open {
+            match rest.find(delimiter) {
+                Some(i) => {
+                    rest = &rest[i + delimiter.len()..];
+                    open = None;
+                    continue;
+                }
+                None => return (open, None),
+            }
+        }
+
+        if c == '#' {
+            return (None, Some(line.len() - rest.len()));
+        }
+        for delimiter in ["\"\"\"", "'''"] {
+            if rest.starts_with(delimiter) {
+                open = Some(delimiter);
+                rest = &rest[delimiter.len()..];
+                continue 'scan;
+            }
+        }
+        if c == '"' || c == '\'' {
+            let mut end = rest.len();
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((i, ch)) = chars.next() {
+                if ch == '\\' {
+                    chars.next();
+                } else if ch == c {
+                    end = i + 1;
+                    break;
+                }
+            }
+            rest = &rest[end..];
+            continue;
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    (open, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(body: &str) -> Section {
+        Section::new(
+            1,
+            "Title".to_string(),
+            format!("# ----\n# 1. Title\n# ----\n{}", body),
+        )
+    }
+
+    #[test]
+    fn indented_comments_are_comment_lines() {
+        let section = section(
+            "# Loops\nfor x in xs:\n    # Skip odd ones\n    if x % 2:\n        continue\n",
+        );
+        let comments: Vec<&str> = section.comment_lines().collect();
+        let code: Vec<&str> = section.code_lines().collect();
+
+        assert_eq!(comments, ["# Loops", "    # Skip odd ones"]);
+        assert_eq!(code, ["for x in xs:", "    if x % 2:", "        continue"]);
+    }
+
+    #[test]
+    fn explained_code_keeps_non_ascii_indentation() {
+        let section = section("# Note\n\u{a0}x = 1\n y = 2\n");
+        assert_eq!(
+            section.explained().content,
+            "# Note ↴\n   \u{a0}x = 1\n    y = 2\n"
+        );
+    }
+
+    #[test]
+    fn lines_ending_in_a_comment_are_code_lines() {
+        let section = section("x = 1  # one\n# A comment # with a hash\n");
+        let comments: Vec<&str> = section.comment_lines().collect();
+        let code: Vec<&str> = section.code_lines().collect();
+
+        assert_eq!(code, ["x = 1  # one"]);
+        assert_eq!(comments, ["# A comment # with a hash"]);
     }
 }
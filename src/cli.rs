@@ -0,0 +1,540 @@
+use crate::config::Config;
+use clap::builder::NonEmptyStringValueParser;
+use clap::{ArgGroup, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use py_cheat::{terminal_width, CheatSheet, ColorDepth, OutputFormat, Theme, BUILTIN_SHEETS};
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+const AFTER_HELP: &str = "\
+Defaults for color, true_color, pager, line_numbers, wrap, width, format,
+theme and plain_headers can be set in a TOML config file, looked up at
+$PY_CHEAT_CONFIG, else $XDG_CONFIG_HOME/py_cheat/config.toml, else
+~/.config/py_cheat/config.toml. Its [aliases] table gives sheets short
+names, so `py_cheat int 2` works, and its [parse] table's marker and
+title_separator set --marker and --title-separator.
+Command-line flags override the config file; favorites saved with `fav add`
+are kept in favorites.json in the same py_cheat directory. Example:
+
+    color = true
+    width = 100
+    pager = false
+    theme = \"monochrome\"
+
+    [aliases]
+    int = \"Intermediate\"
+    adv = \"Advanced\"
+
+Set PY_CHEAT_DEFAULT to a sheet name to have a bare `py_cheat` show that
+sheet's sections instead of every sheet. COLUMNS, when set, overrides the
+config's width and the terminal's (but not --width).
+
+Exit status:
+    0  success
+    1  any other error
+    2  invalid command-line usage
+    3  unknown sheet
+    4  invalid, out-of-range or unknown section
+    5  sheet could not be parsed, or --lint found problems
+    6  file or terminal I/O failed
+    7  invalid config file";
+
+#[derive(Parser)]
+#[command(
+    name = "py_cheat",
+    version,
+    about = "Browse Python cheat sheets in the terminal",
+    allow_negative_numbers = true,
+    after_help = AFTER_HELP,
+    group = ArgGroup::new("query").args(["search", "regex"])
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Sheet to show; omit to list every sheet
+    pub sheet: Option<String>,
+
+    /// Section number, range (e.g. 3 or 3-5), `last`, -N from the end, a
+    /// comma-separated list of these (e.g. 1-3,7), or title substring; 0 prints
+    /// the whole sheet
+    pub section: Option<String>,
+
+    /// Read SHEET (and optionally SECTION, after a space or on the next line)
+    /// from standard input
+    #[arg(long, conflicts_with_all = ["sheet", "section", "query", "all"])]
+    pub stdin: bool,
+
+    /// Print every section whose title or content contains TERM
+    #[arg(short, long, value_name = "TERM", conflicts_with = "sheet")]
+    pub search: Option<String>,
+
+    /// Jump to the section, in any sheet, whose title best matches QUERY
+    /// (typos and prefixes allowed); lists the candidates if there's a tie
+    #[arg(long, value_name = "QUERY", conflicts_with_all = ["sheet", "query"])]
+    pub find: Option<String>,
+
+    /// Print every section whose title or content matches the regular
+    /// expression PATTERN (case-sensitive; prefix with (?i) to ignore case)
+    #[arg(long, value_name = "PATTERN", conflicts_with_all = ["sheet", "search"])]
+    pub regex: Option<String>,
+
+    /// With --search or --regex, print only matching lines plus N lines
+    /// around each
+    #[arg(short = 'C', long, value_name = "N", requires = "query")]
+    pub context: Option<usize>,
+
+    /// With --search or --regex, print only how many sections match in each
+    /// sheet, and the total
+    #[arg(long, requires = "query", conflicts_with = "context")]
+    pub count: bool,
+
+    /// Prefix each printed line with its line number
+    #[arg(short = 'n', long)]
+    pub line_numbers: bool,
+
+    /// Print every sheet in full, one after another
+    #[arg(
+        long,
+        conflicts_with_all = ["sheet", "query", "raw", "copy", "tui", "tree", "section_title", "stats"]
+    )]
+    pub all: bool,
+
+    /// Summarize sections and line counts for every sheet (or just SHEET)
+    #[arg(long, conflicts_with_all = ["section", "query", "raw", "copy", "tui", "tree"])]
+    pub stats: bool,
+
+    /// Print one `N. Title: first line` per section of SHEET, with each
+    /// section's first prose comment, or else its first code line
+    #[arg(long, requires = "sheet", conflicts_with_all = ["section", "query", "raw", "copy", "tui", "tree", "stats"])]
+    pub summary: bool,
+
+    /// Print only the sheet's section tree
+    #[arg(short, long, conflicts_with_all = ["section", "query", "raw", "copy"])]
+    pub tree: bool,
+
+    /// Pick and list sections by the `N.` written in the file instead of by
+    /// position; search results, --find and --stats keep positions, and JSON
+    /// output carries both
+    #[arg(long)]
+    pub authored_numbers: bool,
+
+    /// Print only the function or class NAME defined in the selected section(s)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["query", "raw", "copy", "section_title", "tree", "tui", "all", "stats"])]
+    pub extract: Option<String>,
+
+    /// Print section N of every sheet that has one, to compare how each
+    /// level treats a topic
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["sheet", "query", "all", "raw", "copy", "tui", "fzf", "stats", "extract", "section_title"]
+    )]
+    pub compare: Option<usize>,
+
+    /// Print only the title of the selected section(s)
+    #[arg(long, conflicts_with_all = ["query", "raw", "copy"])]
+    pub section_title: bool,
+
+    /// Browse sheets and sections interactively
+    #[arg(long, conflicts_with_all = ["section", "query", "raw"])]
+    pub tui: bool,
+
+    /// Pick a section (of every sheet, or just SHEET) with fzf and print it
+    #[arg(long, conflicts_with_all = ["section", "query", "raw", "copy", "tui", "all", "stats"])]
+    pub fzf: bool,
+
+    /// Show only runnable code: no section framing or top-level prose comments
+    #[arg(long, conflicts_with_all = ["raw", "tui"])]
+    pub code_only: bool,
+
+    /// Show only non-blank code lines: no framing, comment lines or inline
+    /// comments, for a dense one-screen reference
+    #[arg(long, conflicts_with_all = ["raw", "tui", "code_only", "body_only"])]
+    pub minify: bool,
+
+    /// With --minify, keep comments at the ends of code lines
+    #[arg(long, requires = "minify")]
+    pub keep_inline_comments: bool,
+
+    /// Show sections without their `# ----` rules and `# N. Title` line,
+    /// keeping their comments
+    #[arg(long, conflicts_with_all = ["raw", "tui", "code_only"])]
+    pub body_only: bool,
+
+    /// Pair each explanatory comment with the code below it, indented under
+    /// a `↴` connector
+    #[arg(long, conflicts_with_all = ["raw", "tui", "code_only", "minify", "body_only"])]
+    pub explain: bool,
+
+    /// Draw a box around each section, with its title in the top border
+    #[arg(long = "box", conflicts_with_all = ["raw", "tui"])]
+    pub boxed: bool,
+
+    /// Cut each section's content to at most N bytes, ending with `…`
+    /// [default: unlimited]
+    #[arg(long, value_name = "N")]
+    pub max_bytes: Option<usize>,
+
+    /// Draw indentation visibly, `·` for each space and `→` for each tab
+    #[arg(long)]
+    pub show_whitespace: bool,
+
+    /// Expand tabs in section content to spaces, N columns apart
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..))]
+    pub tab_width: Option<u8>,
+
+    /// Copy the section (or whole sheet) to the clipboard as plain text
+    #[arg(long, conflicts_with_all = ["query", "raw", "tui"])]
+    pub copy: bool,
+
+    /// Soft-wrap long lines to the terminal width
+    #[arg(short, long)]
+    pub wrap: bool,
+
+    /// Lay output out in this many columns instead of $COLUMNS or the
+    /// terminal width (implies --wrap)
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    pub width: Option<usize>,
+
+    /// Draw the section tree with plain `-` bullets instead of box-drawing glyphs
+    #[arg(long)]
+    pub plain_headers: bool,
+
+    /// Cut long section titles in sheet overviews with `…` so each tree line
+    /// fits the output width
+    #[arg(long, global = true)]
+    pub truncate_titles: bool,
+
+    /// Cut long section titles in sheet overviews with `…` so each tree line
+    /// fits this many columns (implies --truncate-titles)
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    pub title_width: Option<usize>,
+
+    /// Plain text for screen readers: no colors, `-` bullets, and a bare
+    /// `N. Title` line in place of each section's comment framing
+    #[arg(long, global = true)]
+    pub plain: bool,
+
+    /// List sections last first in the overview and trees, and print ranges
+    /// and whole sheets (as text) last section first; numbers don't change
+    #[arg(long, global = true)]
+    pub reverse: bool,
+
+    /// Show only these sheets in the overview, e.g. `--only Basics,Advanced`
+    #[arg(
+        long,
+        value_name = "SHEETS",
+        value_delimiter = ',',
+        conflicts_with = "sheet"
+    )]
+    pub only: Vec<String>,
+
+    /// Lay out the sheet overview, or --compare's sections, in as many
+    /// columns as the width allows
+    #[arg(long, visible_alias = "side-by-side")]
+    pub columns: bool,
+
+    /// Check the code of every section (of every sheet, or just SHEET) for
+    /// unbalanced brackets, unclosed strings and bad indentation
+    #[arg(long, conflicts_with_all = ["section", "query", "all", "raw", "copy", "tui", "fzf", "stats", "compare"])]
+    pub lint: bool,
+
+    /// Print the JSON Schema of a sheet (and its sections) as --format json
+    /// prints them
+    #[arg(long, conflicts_with_all = ["sheet", "query", "all", "raw", "copy", "tui", "fzf", "stats", "compare", "highlight"])]
+    pub json_schema: bool,
+
+    /// Highlight any Python file (`-` for standard input) instead of a sheet
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["sheet", "query", "all", "raw", "copy", "tui", "fzf", "stats", "watch", "stdin"]
+    )]
+    pub highlight: Option<PathBuf>,
+
+    /// Print the sheet's file exactly as stored, without parsing or highlighting
+    #[arg(long, conflicts_with_all = ["section", "query"])]
+    pub raw: bool,
+
+    /// Load an extra sheet from a .py file, named after the file stem
+    #[arg(long, global = true, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Redraw whenever the --file sheet is saved, until interrupted
+    #[arg(long, requires = "file", conflicts_with_all = ["tui", "fzf", "copy", "stdin"])]
+    pub watch: bool,
+
+    /// Load every .py file in a directory as an extra sheet
+    #[arg(long, global = true, value_name = "PATH")]
+    pub dir: Option<PathBuf>,
+
+    /// Find --file and --dir sections by one-line headers starting with
+    /// PREFIX, then the number, the title separator and the title
+    #[arg(long, global = true, value_name = "PREFIX", value_parser = NonEmptyStringValueParser::new())]
+    pub marker: Option<String>,
+
+    /// What separates the number from the title in a section header, as
+    /// `:` does in `## 3: Title`
+    #[arg(long, global = true, value_name = "SEP", value_parser = NonEmptyStringValueParser::new())]
+    pub title_separator: Option<String>,
+
+    /// Output format: text, json, jsonl (JSON Lines), markdown or html
+    /// [default: text]
+    #[arg(short, long, global = true, value_parser = str::parse::<OutputFormat>)]
+    pub format: Option<OutputFormat>,
+
+    /// Color theme: default, solarized or monochrome
+    #[arg(long, global = true, value_parser = str::parse::<Theme>)]
+    pub theme: Option<Theme>,
+
+    /// Use 24-bit colors (default when COLORTERM is truecolor or 24bit)
+    #[arg(long, global = true)]
+    pub true_color: bool,
+
+    /// Make URLs in comments and strings clickable in terminals that support
+    /// OSC 8 hyperlinks
+    #[arg(long, global = true)]
+    pub hyperlinks: bool,
+
+    /// Pick out WORD wherever it appears as a whole word in code, in a bold
+    /// emphasis color; repeat to pick out several words
+    #[arg(long, global = true, value_name = "WORD")]
+    pub highlight_keyword: Vec<String>,
+
+    /// Print STRING on its own line between sections shown together, instead
+    /// of a blank line; `rule` draws a rule across the width
+    #[arg(long, global = true, value_name = "STRING")]
+    pub separator: Option<String>,
+
+    /// When to use ANSI colors: auto (only on a terminal, honoring NO_COLOR),
+    /// always (even when piped) or never; a bare --color means always
+    #[arg(
+        long,
+        global = true,
+        value_name = "WHEN",
+        default_value = "auto",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    pub color: ColorChoice,
+
+    /// Disable ANSI colors, as --color=never does
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Write the output to PATH (created or overwritten) instead of the
+    /// terminal, without colors unless --color=always
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Never pipe long output through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print sheet names one per line
+    List {
+        #[command(flatten)]
+        page: Page,
+    },
+
+    /// Print a randomly chosen section, optionally from one sheet only
+    Random { sheet: Option<String> },
+
+    /// Quiz yourself: guess each section's title from its code, in random
+    /// order; `q` quits and prints the score
+    Quiz { sheet: String },
+
+    /// Print one table of contents of every sheet's sections (with
+    /// --format markdown, linking into `--all --format markdown`)
+    Toc {
+        #[command(flatten)]
+        page: Page,
+    },
+
+    /// List section titles found in only one of two sheets
+    Diff { left: String, right: String },
+
+    /// Print a bookmarked section by number, or manage bookmarks
+    #[command(args_conflicts_with_subcommands = true)]
+    Fav {
+        #[command(subcommand)]
+        action: Option<FavAction>,
+
+        /// Favorite to print, as numbered by `fav list`; omit to list them
+        index: Option<usize>,
+    },
+
+    /// Print a completion script for the given shell, or for $SHELL
+    ///
+    /// Install it by writing the script where your shell looks for completions:
+    ///
+    ///   bash: py_cheat completions bash > ~/.local/share/bash-completion/completions/py_cheat
+    ///   zsh:  py_cheat completions zsh > ~/.zfunc/_py_cheat  (with ~/.zfunc in $fpath)
+    ///   fish: py_cheat completions fish > ~/.config/fish/completions/py_cheat.fish
+    #[command(verbatim_doc_comment)]
+    Completions { shell: Option<Shell> },
+
+    /// Print a man page (roff) generated from these options
+    ///
+    /// Install it with: py_cheat mangen > /usr/share/man/man1/py_cheat.1
+    #[command(verbatim_doc_comment)]
+    Mangen,
+}
+
+#[derive(Subcommand)]
+pub enum FavAction {
+    /// Bookmark a section
+    Add { sheet: String, section: usize },
+
+    /// List favorites with their current titles
+    List,
+
+    /// Forget a favorite
+    Remove { index: usize },
+}
+
+// A slice of a listing, for tools paging through it
+#[derive(Args)]
+pub struct Page {
+    /// Skip the first N entries; past the end leaves none
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub offset: usize,
+
+    /// Print at most M entries
+    #[arg(long, value_name = "M")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    // The shell $SHELL runs, going by the program's name
+    pub fn detect() -> Option<Self> {
+        let path = PathBuf::from(env::var_os("SHELL")?);
+        let name = path.file_name()?.to_str()?;
+        Self::from_str(name, true).ok()
+    }
+}
+
+impl Cli {
+    // Like `Cli::parse`, with the built-in sheets and the config's aliases for
+    // them listed at the end of --help. A config that fails to load is
+    // reported after parsing, so here it just lists no aliases.
+    pub fn parse_with_sheet_list() -> Self {
+        let aliases = Config::load()
+            .map(|config| config.aliases)
+            .unwrap_or_default();
+        let matches = Cli::command()
+            .after_help(format!("{}\n\n{}", AFTER_HELP, sheet_list(&aliases)))
+            .get_matches();
+        Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    // The command as `--help` describes it, built-in sheets included
+    pub fn command_with_sheet_list() -> clap::Command {
+        let after_help = format!("{}\n\n{}", AFTER_HELP, sheet_list(&BTreeMap::new()));
+        Cli::command().after_help(after_help)
+    }
+
+    // Width that wrapping, overview columns and --compare lay output out in:
+    // --width, then $COLUMNS, then the config's width, then the terminal's,
+    // measured once here. None when stdout isn't a terminal and nothing sets
+    // a width.
+    pub fn width(&self, config: &Config) -> Option<usize> {
+        let columns_var = env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.trim().parse().ok())
+            .filter(|&columns| columns > 0);
+        self.width
+            .or(columns_var)
+            .or(config.width)
+            .or_else(|| io::stdout().is_terminal().then(terminal_width))
+    }
+
+    // Width overview tree lines are cut to: --title-width, else the output
+    // width (or 80) with --truncate-titles
+    pub fn title_width(&self, config: &Config) -> Option<usize> {
+        self.title_width.or_else(|| {
+            self.truncate_titles
+                .then(|| self.width(config).unwrap_or_else(terminal_width))
+        })
+    }
+
+    // --wrap or the config's `wrap`; --width or the config's width also
+    // implies wrapping
+    pub fn wrap(&self, config: &Config) -> bool {
+        self.wrap || config.wrap == Some(true) || self.width.or(config.width).is_some()
+    }
+
+    // --true-color, then the config's `true_color`, then COLORTERM
+    pub fn color_depth(&self, config: &Config) -> ColorDepth {
+        match (self.true_color, config.true_color) {
+            (true, _) | (false, Some(true)) => ColorDepth::TrueColor,
+            (false, Some(false)) => ColorDepth::Ansi16,
+            (false, None) => ColorDepth::detect(),
+        }
+    }
+
+    // --color=never, --no-color and --plain always turn color off and
+    // --color=always on. Otherwise color is used only when not disabled by a
+    // non-empty NO_COLOR or `color = false` in the config, and output goes to
+    // a terminal rather than --output's file.
+    pub fn color_enabled(&self, config: &Config) -> bool {
+        if self.no_color || self.plain || self.color == ColorChoice::Never {
+            return false;
+        }
+        if self.color == ColorChoice::Always {
+            return true;
+        }
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        !no_color_env
+            && config.color != Some(false)
+            && self.output.is_none()
+            && io::stdout().is_terminal()
+    }
+}
+
+fn sheet_list(aliases: &BTreeMap<String, String>) -> String {
+    let width = BUILTIN_SHEETS
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let mut list = String::from("Sheets (add more with --file or --dir):\n");
+    for (name, content) in BUILTIN_SHEETS {
+        let count = CheatSheet::parse(content).map_or(0, |sheet| sheet.sections.len());
+        let names: Vec<&str> = aliases
+            .iter()
+            .filter(|(_, sheet)| sheet.eq_ignore_ascii_case(name))
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        let names = match names.is_empty() {
+            true => String::new(),
+            false => format!(" (alias {})", names.join(", ")),
+        };
+        list.push_str(&format!(
+            "    {:<width$}  {} sections{}\n",
+            name, count, names
+        ));
+    }
+    list.push_str("Run `py_cheat SHEET` to see a sheet's sections.");
+    list
+}
@@ -0,0 +1,7 @@
+use arboard::Clipboard;
+
+// Puts plain text on the system clipboard. On X11 the selection lives only as
+// long as its owner, so this relies on a clipboard manager taking it over.
+pub fn copy(text: &str) -> Result<(), arboard::Error> {
+    Clipboard::new()?.set_text(text)
+}
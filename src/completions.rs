@@ -0,0 +1,195 @@
+use crate::cli::{Cli, Shell};
+use clap::CommandFactory;
+use py_cheat::{CheatError, CheatSheetManager, OutputFormat, Theme};
+
+const SHELLS: &str = "bash zsh fish";
+
+struct CompletionData {
+    // (sheet name, section numbers including 0 for the whole sheet)
+    sheets: Vec<(String, String)>,
+    subcommands: Vec<String>,
+    flags: Vec<String>,
+    value_flags: Vec<String>,
+    formats: String,
+    themes: String,
+}
+
+impl CompletionData {
+    fn collect(manager: &CheatSheetManager) -> Result<Self, CheatError> {
+        let sheets = manager
+            .sheet_names()
+            .map(|name| {
+                let count = manager.parse_sheet(name)?.sections.len();
+                let numbers: Vec<String> = (0..=count).map(|n| n.to_string()).collect();
+                Ok((name.to_string(), numbers.join(" ")))
+            })
+            .collect::<Result<Vec<_>, CheatError>>()?;
+
+        let mut command = Cli::command();
+        command.build();
+
+        let subcommands = command
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set())
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+
+        let mut flags = Vec::new();
+        let mut value_flags = Vec::new();
+        for arg in command.get_arguments().filter(|arg| !arg.is_positional()) {
+            let names: Vec<String> = arg
+                .get_long()
+                .map(|long| format!("--{}", long))
+                .into_iter()
+                .chain(arg.get_short().map(|short| format!("-{}", short)))
+                .collect();
+            if arg.get_action().takes_values() {
+                value_flags.extend(names.iter().cloned());
+            }
+            flags.extend(names);
+        }
+
+        Ok(Self {
+            sheets,
+            subcommands,
+            flags,
+            value_flags,
+            formats: OutputFormat::NAMES.join(" "),
+            themes: Theme::NAMES.join(" "),
+        })
+    }
+}
+
+pub fn generate(shell: Shell, manager: &CheatSheetManager) -> Result<String, CheatError> {
+    let data = CompletionData::collect(manager)?;
+    Ok(match shell {
+        Shell::Bash => bash(&data),
+        Shell::Zsh => zsh(&data),
+        Shell::Fish => fish(&data),
+    })
+}
+
+fn sheet_names(data: &CompletionData) -> String {
+    let names: Vec<&str> = data.sheets.iter().map(|(name, _)| name.as_str()).collect();
+    names.join(" ")
+}
+
+fn bash(data: &CompletionData) -> String {
+    let section_cases: String = data
+        .sheets
+        .iter()
+        .map(|(name, numbers)| {
+            format!(
+                "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+                name, numbers
+            )
+        })
+        .collect();
+
+    format!(
+        r#"_py_cheat() {{
+    local cur prev
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    case "$prev" in
+        --format|-f) COMPREPLY=($(compgen -W "{formats}" -- "$cur")); return ;;
+        --theme) COMPREPLY=($(compgen -W "{themes}" -- "$cur")); return ;;
+        --search|-s) return ;;
+        completions) COMPREPLY=($(compgen -W "{shells}" -- "$cur")); return ;;
+{section_cases}    esac
+
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{flags}" -- "$cur"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{sheets} {subcommands}" -- "$cur"))
+    fi
+}}
+complete -F _py_cheat py_cheat
+"#,
+        formats = data.formats,
+        themes = data.themes,
+        shells = SHELLS,
+        section_cases = section_cases,
+        flags = data.flags.join(" "),
+        sheets = sheet_names(data),
+        subcommands = data.subcommands.join(" "),
+    )
+}
+
+fn zsh(data: &CompletionData) -> String {
+    let section_cases: String = data
+        .sheets
+        .iter()
+        .map(|(name, numbers)| format!("        {}) compadd -- {} ;;\n", name, numbers))
+        .collect();
+
+    format!(
+        r#"#compdef py_cheat
+
+_py_cheat() {{
+    case "${{words[CURRENT-1]}}" in
+        --format|-f) compadd -- {formats}; return ;;
+        --theme) compadd -- {themes}; return ;;
+        --search|-s) return ;;
+    esac
+
+    if [[ "$PREFIX" == -* ]]; then
+        compadd -- {flags}
+        return
+    fi
+
+    if (( CURRENT == 2 )); then
+        compadd -- {sheets} {subcommands}
+        return
+    fi
+
+    case "${{words[2]}}" in
+        completions) compadd -- {shells} ;;
+{section_cases}    esac
+}}
+
+_py_cheat "$@"
+"#,
+        formats = data.formats,
+        themes = data.themes,
+        flags = data.flags.join(" "),
+        sheets = sheet_names(data),
+        subcommands = data.subcommands.join(" "),
+        shells = SHELLS,
+        section_cases = section_cases,
+    )
+}
+
+fn fish(data: &CompletionData) -> String {
+    let mut script = String::from("complete -c py_cheat -f\n");
+
+    script.push_str(&format!(
+        "complete -c py_cheat -n '__fish_use_subcommand' -a '{} {}'\n",
+        sheet_names(data),
+        data.subcommands.join(" ")
+    ));
+    script.push_str(&format!(
+        "complete -c py_cheat -n '__fish_seen_subcommand_from completions' -a '{}'\n",
+        SHELLS
+    ));
+    for (name, numbers) in &data.sheets {
+        script.push_str(&format!(
+            "complete -c py_cheat -n '__fish_seen_subcommand_from {}' -a '{}'\n",
+            name, numbers
+        ));
+    }
+
+    for flag in data.flags.iter().filter_map(|flag| flag.strip_prefix("--")) {
+        let takes_value = data.value_flags.iter().any(|v| v == &format!("--{}", flag));
+        let values = match flag {
+            "format" => format!(" -x -a '{}'", data.formats),
+            "theme" => format!(" -x -a '{}'", data.themes),
+            _ if takes_value => " -x".to_string(),
+            _ => String::new(),
+        };
+        script.push_str(&format!("complete -c py_cheat -l {}{}\n", flag, values));
+    }
+
+    script
+}
@@ -0,0 +1,111 @@
+use py_cheat::{CheatError, OutputFormat, Theme};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Defaults read from a TOML file. The first of these that is set is used:
+//   $PY_CHEAT_CONFIG
+//   $XDG_CONFIG_HOME/py_cheat/config.toml
+//   ~/.config/py_cheat/config.toml
+// A missing file is the same as an empty one. Command-line flags always win
+// over values here.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub color: Option<bool>,
+    pub true_color: Option<bool>,
+    pub pager: Option<bool>,
+    pub line_numbers: Option<bool>,
+    pub wrap: Option<bool>,
+    pub width: Option<usize>,
+    pub format: Option<String>,
+    pub theme: Option<String>,
+    pub plain_headers: Option<bool>,
+    // `[aliases]` table of short names for sheets, such as `int = "Intermediate"`
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub parse: ParseConfig,
+}
+
+// `[parse]` table of section markers for sheets loaded with --file or --dir,
+// such as `marker = "## "` and `title_separator = ":"` for `## 3: Title`
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParseConfig {
+    pub marker: Option<String>,
+    pub title_separator: Option<String>,
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        match non_empty_var("PY_CHEAT_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(Self::dir()?.join("config.toml")),
+        }
+    }
+
+    // $XDG_CONFIG_HOME/py_cheat, else ~/.config/py_cheat, which also holds
+    // other saved state such as favorites
+    pub fn dir() -> Option<PathBuf> {
+        let config_home = non_empty_var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty_var("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("py_cheat"))
+    }
+
+    pub fn load() -> Result<Self, CheatError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(CheatError::Io { path, source }),
+        };
+
+        let config: Self = toml::from_str(&content).map_err(|e| CheatError::Config {
+            path: path.clone(),
+            message: e.message().to_string(),
+        })?;
+        if let Some(format) = &config.format {
+            format
+                .parse::<OutputFormat>()
+                .map_err(|message| CheatError::Config {
+                    path: path.clone(),
+                    message,
+                })?;
+        }
+        let markers = [&config.parse.marker, &config.parse.title_separator];
+        if markers.iter().any(|marker| marker.as_deref() == Some("")) {
+            return Err(CheatError::Config {
+                path,
+                message: "parse.marker and parse.title_separator can't be empty".to_string(),
+            });
+        }
+        if let Some(theme) = &config.theme {
+            theme
+                .parse::<Theme>()
+                .map_err(|message| CheatError::Config { path, message })?;
+        }
+        Ok(config)
+    }
+
+    pub fn format(&self) -> Option<OutputFormat> {
+        self.format
+            .as_deref()
+            .and_then(|format| format.parse().ok())
+    }
+
+    pub fn theme(&self) -> Option<Theme> {
+        self.theme.as_deref().and_then(|theme| theme.parse().ok())
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<OsString> {
+    env::var_os(name).filter(|value| !value.is_empty())
+}
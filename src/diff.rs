@@ -0,0 +1,73 @@
+use crate::cheatsheet::CheatSheet;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct DiffEntry {
+    pub number: usize,
+    pub title: String,
+}
+
+// Section titles found in only one of two sheets. Titles are compared
+// ignoring case and surrounding whitespace; numbers are positions in the
+// sheet the title comes from.
+#[derive(Serialize)]
+pub struct SheetDiff {
+    pub left: String,
+    pub right: String,
+    pub only_left: Vec<DiffEntry>,
+    pub only_right: Vec<DiffEntry>,
+}
+
+impl SheetDiff {
+    pub fn compare(
+        left: &str,
+        left_sheet: &CheatSheet,
+        right: &str,
+        right_sheet: &CheatSheet,
+    ) -> Self {
+        Self {
+            left: left.to_string(),
+            right: right.to_string(),
+            only_left: missing_from(left_sheet, right_sheet),
+            only_right: missing_from(right_sheet, left_sheet),
+        }
+    }
+
+    // `---`/`+++` header naming the sheets, then `-` lines for titles only in
+    // the left sheet and `+` lines for titles only in the right
+    pub fn render(&self) -> String {
+        let mut output = format!("--- {}\n+++ {}\n", self.left, self.right);
+        if self.only_left.is_empty() && self.only_right.is_empty() {
+            output.push_str("(no differences in section titles)\n");
+        }
+        for (sign, entries) in [('-', &self.only_left), ('+', &self.only_right)] {
+            for entry in entries {
+                output.push_str(&format!("{} {}. {}\n", sign, entry.number, entry.title));
+            }
+        }
+        output
+    }
+}
+
+fn normalize(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+// Sections of `sheet` whose title doesn't appear in `other`
+fn missing_from(sheet: &CheatSheet, other: &CheatSheet) -> Vec<DiffEntry> {
+    let other_titles: Vec<String> = other
+        .sections
+        .iter()
+        .map(|section| normalize(&section.title))
+        .collect();
+    sheet
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| !other_titles.contains(&normalize(&section.title)))
+        .map(|(i, section)| DiffEntry {
+            number: i + 1,
+            title: section.title.clone(),
+        })
+        .collect()
+}
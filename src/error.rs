@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CheatError {
+    SheetNotFound {
+        name: String,
+        suggestion: Option<String>,
+    },
+    // Names given to --only that aren't sheets, and the sheets there are
+    UnknownSheets {
+        names: Vec<String>,
+        known: Vec<String>,
+    },
+    InvalidSectionNumber(String),
+    InvalidSectionRange(String),
+    SectionOutOfRange {
+        given: usize,
+        max: usize,
+    },
+    // A negative section number whose distance from the end exceeds the count
+    SectionFromEndOutOfRange {
+        given: usize,
+        max: usize,
+    },
+    SectionNotFound(String),
+    // No section has this `N.` in its title line (with authored numbering)
+    NoSectionNumbered(usize),
+    // No `def`/`class` of this name in the selected section(s)
+    DefinitionNotFound(String),
+    NoSections,
+    AmbiguousSection {
+        query: String,
+        candidates: Vec<(usize, String)>,
+    },
+    // (position, number written in the file) for each out-of-sequence section
+    SectionNumbering(Vec<(usize, String)>),
+    EmptySearchTerm,
+    InvalidRegex(regex::Error),
+    ParseFailed(String),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Write {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Json(serde_json::Error),
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    Terminal(io::Error),
+    // --lint found this many problems, already reported
+    LintProblems(usize),
+    // --watch couldn't follow the file for changes
+    Watch {
+        path: PathBuf,
+        source: notify::Error,
+    },
+    // fzf couldn't be started, usually because it isn't installed
+    Fzf(io::Error),
+    FavoriteOutOfRange {
+        given: usize,
+        max: usize,
+    },
+    // A saved favorite whose sheet or section can no longer be shown
+    StaleFavorite {
+        index: usize,
+        reason: Box<CheatError>,
+    },
+}
+
+impl CheatError {
+    // Whether this is a failed write to a reader that stopped early, as
+    // `head` does, which isn't worth reporting
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(self, CheatError::Write { source, .. } if source.kind() == io::ErrorKind::BrokenPipe)
+    }
+
+    // Process exit status for this error, so scripts can tell failures apart.
+    // 2 is left to clap for command-line usage errors; keep in sync with the
+    // list in `--help`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CheatError::SheetNotFound { .. } | CheatError::UnknownSheets { .. } => 3,
+            CheatError::InvalidSectionNumber(_)
+            | CheatError::InvalidSectionRange(_)
+            | CheatError::SectionOutOfRange { .. }
+            | CheatError::SectionFromEndOutOfRange { .. }
+            | CheatError::SectionNotFound(_)
+            | CheatError::NoSectionNumbered(_)
+            | CheatError::DefinitionNotFound(_)
+            | CheatError::NoSections
+            | CheatError::AmbiguousSection { .. }
+            | CheatError::FavoriteOutOfRange { .. } => 4,
+            CheatError::SectionNumbering(_)
+            | CheatError::ParseFailed(_)
+            | CheatError::LintProblems(_) => 5,
+            CheatError::Io { .. }
+            | CheatError::Write { .. }
+            | CheatError::Terminal(_)
+            | CheatError::Watch { .. }
+            | CheatError::Fzf(_) => 6,
+            CheatError::Config { .. } => 7,
+            CheatError::StaleFavorite { reason, .. } => reason.exit_code(),
+            CheatError::EmptySearchTerm | CheatError::InvalidRegex(_) | CheatError::Json(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CheatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheatError::SheetNotFound {
+                name,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Could not find sheet {}. Did you mean: {}?",
+                name, suggestion
+            ),
+            CheatError::SheetNotFound { name, .. } => write!(f, "Could not find sheet {}", name),
+            CheatError::UnknownSheets { names, known } => write!(
+                f,
+                "Unknown sheet{} {} (known sheets: {})",
+                if names.len() == 1 { "" } else { "s" },
+                names.join(", "),
+                known.join(", ")
+            ),
+            CheatError::InvalidSectionNumber(given) => write!(
+                f,
+                "Section number must be a positive integer, got '{}'",
+                given
+            ),
+            CheatError::InvalidSectionRange(given) => write!(
+                f,
+                "Invalid section range '{}' (expected START-END with START <= END, e.g. 3-5)",
+                given
+            ),
+            CheatError::SectionOutOfRange { given, max } => write!(
+                f,
+                "Invalid section number {} (sheet has sections 1-{})",
+                given, max
+            ),
+            CheatError::SectionFromEndOutOfRange { given, max } => write!(
+                f,
+                "Invalid section number -{} (sheet has sections -1 to -{} from the end)",
+                given, max
+            ),
+            CheatError::NoSections => write!(f, "Sheet has no sections"),
+            CheatError::SectionNotFound(query) => {
+                write!(f, "No section title contains '{}'", query)
+            }
+            CheatError::NoSectionNumbered(number) => {
+                write!(f, "No section is numbered {} in the sheet", number)
+            }
+            CheatError::DefinitionNotFound(name) => write!(
+                f,
+                "No `def {0}` or `class {0}` in the selected section",
+                name
+            ),
+            CheatError::AmbiguousSection { query, candidates } => {
+                write!(f, "'{}' matches several sections:", query)?;
+                for (number, title) in candidates {
+                    write!(f, "\n  {}. {}", number, title)?;
+                }
+                Ok(())
+            }
+            CheatError::SectionNumbering(mismatches) => {
+                let details: Vec<String> = mismatches
+                    .iter()
+                    .map(|(position, number)| {
+                        format!("section {} is numbered {}", position, number)
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Section numbers should run 1, 2, 3...: {}",
+                    details.join(", ")
+                )
+            }
+            CheatError::EmptySearchTerm => write!(f, "Search term must not be empty"),
+            CheatError::InvalidRegex(e) => write!(f, "Invalid regular expression: {}", e),
+            CheatError::ParseFailed(reason) => write!(f, "Could not parse sheet: {}", reason),
+            CheatError::Io { path, source } => {
+                write!(f, "Could not read {}: {}", path.display(), source)
+            }
+            CheatError::Write { path, source } => {
+                write!(f, "Could not write {}: {}", path.display(), source)
+            }
+            CheatError::Json(e) => write!(f, "Could not serialize JSON: {}", e),
+            CheatError::Config { path, message } => {
+                write!(f, "Invalid config file {}: {}", path.display(), message)
+            }
+            CheatError::Terminal(e) => write!(f, "Terminal error: {}", e),
+            CheatError::LintProblems(count) => {
+                let plural = if *count == 1 { "" } else { "s" };
+                write!(f, "--lint found {} problem{}", count, plural)
+            }
+            CheatError::Watch { path, source } => {
+                write!(f, "Could not watch {}: {}", path.display(), source)
+            }
+            CheatError::Fzf(e) if e.kind() == io::ErrorKind::NotFound => write!(
+                f,
+                "--fzf needs fzf on your PATH; install it from https://github.com/junegunn/fzf"
+            ),
+            CheatError::Fzf(e) => write!(f, "Could not run fzf: {}", e),
+            CheatError::FavoriteOutOfRange { max: 0, .. } => write!(
+                f,
+                "No favorites saved; add one with `py_cheat fav add SHEET SECTION`"
+            ),
+            CheatError::FavoriteOutOfRange { given, max } => {
+                write!(
+                    f,
+                    "No favorite {} (favorites are numbered 1-{})",
+                    given, max
+                )
+            }
+            CheatError::StaleFavorite { index, reason } => {
+                write!(f, "Favorite {} no longer exists: {}", index, reason)
+            }
+        }
+    }
+}
+
+impl Error for CheatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheatError::Io { source, .. } | CheatError::Write { source, .. } => Some(source),
+            CheatError::InvalidRegex(e) => Some(e),
+            CheatError::Json(e) => Some(e),
+            CheatError::Watch { source, .. } => Some(source),
+            CheatError::Terminal(e) | CheatError::Fzf(e) => Some(e),
+            CheatError::StaleFavorite { reason, .. } => Some(reason.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for CheatError {
+    fn from(e: serde_json::Error) -> Self {
+        CheatError::Json(e)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::config::Config;
+use py_cheat::{CheatError, CheatSheetManager};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// A bookmarked section. Favorites are kept as a JSON list in favorites.json
+// next to the config file and are numbered from 1 in the order added.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub sheet: String,
+    pub section: usize,
+}
+
+fn path() -> Option<PathBuf> {
+    Config::dir().map(|dir| dir.join("favorites.json"))
+}
+
+// A missing file means no favorites yet
+pub fn load() -> Result<Vec<Favorite>, CheatError> {
+    let Some(path) = path() else {
+        return Ok(Vec::new());
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(CheatError::Io { path, source }),
+    };
+    serde_json::from_str(&content).map_err(|e| CheatError::Config {
+        path,
+        message: e.to_string(),
+    })
+}
+
+fn save(favorites: &[Favorite]) -> Result<(), CheatError> {
+    let path = path().ok_or_else(|| CheatError::Write {
+        path: PathBuf::from("favorites.json"),
+        source: io::Error::new(
+            io::ErrorKind::NotFound,
+            "no config directory (set XDG_CONFIG_HOME or HOME)",
+        ),
+    })?;
+    let write_error = |source| CheatError::Write {
+        path: path.clone(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(write_error)?;
+    }
+    let content = serde_json::to_string_pretty(favorites)?;
+    fs::write(&path, content + "\n").map_err(write_error)
+}
+
+// The title of a favorite's section, or None if the sheet or section is gone
+fn title<'a>(manager: &'a CheatSheetManager, favorite: &Favorite) -> Option<&'a str> {
+    let cheat_sheet = manager.parse_sheet(&favorite.sheet).ok()?;
+    let section = cheat_sheet.sections.get(favorite.section.checked_sub(1)?)?;
+    Some(&section.title)
+}
+
+pub fn add(manager: &CheatSheetManager, sheet: &str, section: usize) -> Result<(), CheatError> {
+    let count = manager.parse_sheet(sheet)?.sections.len();
+    if section == 0 || section > count {
+        return Err(CheatError::SectionOutOfRange {
+            given: section,
+            max: count,
+        });
+    }
+    // Stored under the sheet's own spelling, whatever case was typed
+    let sheet = manager
+        .sheet_names()
+        .find(|name| name.eq_ignore_ascii_case(sheet))
+        .unwrap_or(sheet);
+    let favorite = Favorite {
+        sheet: sheet.to_string(),
+        section,
+    };
+
+    let mut favorites = load()?;
+    if let Some(i) = favorites.iter().position(|existing| *existing == favorite) {
+        eprintln!("{} {} is already favorite {}", sheet, section, i + 1);
+        return Ok(());
+    }
+    favorites.push(favorite);
+    save(&favorites)?;
+    eprintln!(
+        "Added {} {} as favorite {}",
+        sheet,
+        section,
+        favorites.len()
+    );
+    Ok(())
+}
+
+pub fn remove(index: usize) -> Result<(), CheatError> {
+    let mut favorites = load()?;
+    let favorite = checked_index(index, favorites.len())?;
+    let removed = favorites.remove(favorite);
+    save(&favorites)?;
+    eprintln!(
+        "Removed favorite {} ({} {})",
+        index, removed.sheet, removed.section
+    );
+    Ok(())
+}
+
+// Numbered favorites with their current titles; ones whose section has since
+// disappeared are flagged rather than dropped
+pub fn render_list(manager: &CheatSheetManager) -> Result<String, CheatError> {
+    let favorites = load()?;
+    if manager.format().is_json() {
+        return manager.render_json(&favorites);
+    }
+    if favorites.is_empty() {
+        return Ok("No favorites yet; add one with `py_cheat fav add SHEET SECTION`\n".to_string());
+    }
+
+    let mut output = String::new();
+    for (i, favorite) in favorites.iter().enumerate() {
+        let title = title(manager, favorite).unwrap_or("(no longer exists)");
+        output.push_str(&format!(
+            "{}. {} {}: {}\n",
+            i + 1,
+            favorite.sheet,
+            favorite.section,
+            title
+        ));
+    }
+    Ok(output)
+}
+
+// The favorite's section, shown as `py_cheat SHEET SECTION` shows it. It's
+// stored by position, so it's looked up by position whatever the numbering.
+pub fn show(manager: &CheatSheetManager, index: usize) -> Result<(), CheatError> {
+    let favorites = load()?;
+    let favorite = &favorites[checked_index(index, favorites.len())?];
+    manager
+        .show_section_at(&favorite.sheet, favorite.section)
+        .map_err(|reason| CheatError::StaleFavorite {
+            index,
+            reason: Box::new(reason),
+        })
+}
+
+fn checked_index(index: usize, count: usize) -> Result<usize, CheatError> {
+    if index == 0 || index > count {
+        return Err(CheatError::FavoriteOutOfRange {
+            given: index,
+            max: count,
+        });
+    }
+    Ok(index - 1)
+}
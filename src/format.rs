@@ -0,0 +1,54 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Output formats selectable with `--format`. An unknown value is reported on
+// stderr and the process exits non-zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    // JSON Lines: one compact JSON value per line, a line per item of a list
+    JsonLines,
+    Markdown,
+    Html,
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 5] = ["text", "json", "jsonl", "markdown", "html"];
+
+    pub fn is_json(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::JsonLines)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" | "json-lines" | "ndjson" => Ok(OutputFormat::JsonLines),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            _ => Err(format!(
+                "Unknown format '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::JsonLines => write!(f, "jsonl"),
+            OutputFormat::Markdown => write!(f, "markdown"),
+            OutputFormat::Html => write!(f, "html"),
+        }
+    }
+}
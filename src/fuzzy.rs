@@ -0,0 +1,72 @@
+// Edit distance between two strings, counted in chars
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut current = vec![0; b_chars.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b_chars.len()]
+}
+
+// Picks the candidate closest to `input`, ignoring case, if it is near enough
+// to plausibly be a typo
+pub fn closest_match<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(&input, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.chars().count() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// How well `text` matches `query`, word by word, ignoring case: lower is
+// better, None is no match. Every query word has to match some word of the
+// text exactly (0), as a prefix (1), inside it (2), or within a small edit
+// distance of it or of its start (3 and up), so `slic` finds `SLICING`.
+pub fn word_score(query: &str, text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .map(|wanted| {
+            words
+                .iter()
+                .filter_map(|word| single_word_score(wanted, word))
+                .min()
+        })
+        .sum()
+}
+
+fn single_word_score(wanted: &str, word: &str) -> Option<usize> {
+    if word == wanted {
+        return Some(0);
+    }
+    if word.starts_with(wanted) {
+        return Some(1);
+    }
+    if word.contains(wanted) {
+        return Some(2);
+    }
+
+    let length = wanted.chars().count();
+    let prefix: String = word.chars().take(length).collect();
+    let distance = levenshtein(wanted, word).min(levenshtein(wanted, &prefix));
+    (distance <= (length / 4).max(1)).then_some(3 + distance)
+}
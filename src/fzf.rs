@@ -0,0 +1,26 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+// Lets the user choose one of `entries` (one per line) with fzf, which draws
+// on the terminal itself. None when the choice is cancelled with Esc/Ctrl-C.
+pub fn pick(entries: &[String]) -> io::Result<Option<String>> {
+    let mut child = Command::new("fzf")
+        .args(["--no-multi", "--prompt", "py_cheat> "])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // fzf may exit before reading everything, which isn't an error
+        let _ = stdin.write_all(entries.join("\n").as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let choice = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    Ok(Some(choice).filter(|choice| !choice.is_empty()))
+}
@@ -1,3 +1,5 @@
 pub mod python;
+pub mod theme;
 
-pub use python::PythonHighlighter;
+pub use python::{PythonHighlighter, Token, TokenKind};
+pub use theme::{ColorDepth, Theme, TokenCategory};
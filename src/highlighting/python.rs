@@ -1,104 +1,802 @@
-use syntect::easy::HighlightLines;
-use syntect::highlighting::{Color, Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use super::theme::{ColorDepth, Theme, TokenCategory, TokenStyle};
+use crate::cheatsheet::section::{fence_marker, is_foreign};
+use crate::html;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use syntect::easy::ScopeRegionIterator;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
 use syntect::util::LinesWithEndings;
 
+const MATCH_EMPHASIS: &str = "\x1b[7m"; // Reverse video for search matches
+
+// OSC 8 hyperlink escapes around a link's text; the URL goes between the
+// opening's `;;` and its terminator
+const HYPERLINK_OPEN: &str = "\x1b]8;;";
+const HYPERLINK_CLOSE: &str = "\x1b]8;;\x1b\\";
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+// How --show-whitespace draws indentation
+const SPACE_GLYPH: &str = "·";
+const TAB_GLYPH: &str = "→";
+
+// Scope prefixes from the Python grammar and the category each one means. A
+// token takes the category of its innermost scope matching any rule, checked
+// in this order, so the more specific prefixes must come first.
+const SCOPE_RULES: &[(&str, TokenCategory)] = &[
+    // Docstrings are strings, whatever the grammar calls them
+    ("comment.block.documentation", TokenCategory::String),
+    ("comment", TokenCategory::Comment),
+    ("storage.type.string", TokenCategory::String),
+    // Escapes such as an f-string's `{{` read as part of the string
+    ("constant.character.escape", TokenCategory::String),
+    ("string", TokenCategory::String),
+    // An interpolation's expression keeps its own categories; only the
+    // braces stand out
+    (
+        "punctuation.section.interpolation",
+        TokenCategory::Interpolation,
+    ),
+    // Suffixes like the `j` of `1j` belong to the number
+    ("storage.type.numeric", TokenCategory::Number),
+    ("constant.numeric", TokenCategory::Number),
+    ("constant", TokenCategory::Constant),
+    ("entity.name.function", TokenCategory::Function),
+    ("entity.name.class", TokenCategory::Class),
+    // The grammar only tags unqualified names, so `obj.range` stays plain
+    ("support.function.builtin", TokenCategory::Builtin),
+    ("support.type", TokenCategory::Builtin),
+    ("keyword.operator.logical", TokenCategory::Keyword),
+    ("keyword.operator", TokenCategory::Plain),
+    ("keyword", TokenCategory::Keyword),
+    ("storage", TokenCategory::Keyword),
+];
+
+// A decorator line, and the parts of it that are its arguments rather than
+// its name. `@` as the matrix-multiply operator is `keyword.operator` instead.
+const DECORATOR_SCOPE: &str = "meta.annotation";
+const DECORATOR_ARGUMENT_SCOPES: &[&str] =
+    &["meta.annotation.arguments", "punctuation.section.arguments"];
+
+// What a highlighted range of the input is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    // A section's `# ----` rule or `# N. Title` line
+    Header,
+    // Python source; identifiers, operators and whitespace are `Plain`
+    Code(TokenCategory),
+    // A line the grammar failed on, passed through as is
+    Unparsed,
+    // A line inside a fence for another language, left uncolored
+    Foreign,
+}
+
+// A run of the input that's drawn alike, for renderers of their own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+}
+
+// What carries over from one line to the next while highlighting
+struct LineState {
+    parse_state: ParseState,
+    stack: ScopeStack,
+    in_fence: bool,
+}
+
 pub struct PythonHighlighter {
     syntax_set: SyntaxSet,
-    theme_set: ThemeSet,
+    scope_rules: Vec<(Scope, TokenCategory)>,
+    decorator_scope: Scope,
+    decorator_argument_scopes: Vec<Scope>,
+    theme: Theme,
+    depth: ColorDepth,
+    color: bool,
+    show_whitespace: bool,
+    hyperlinks: bool,
+    emphasized_words: Vec<String>,
 }
 
 impl PythonHighlighter {
-    pub fn new() -> Self {
+    pub fn new(theme: Theme) -> Self {
+        Self::with_theme(theme, true)
+    }
+
+    pub fn with_color(color: bool) -> Self {
+        Self::with_theme(Theme::default(), color)
+    }
+
+    // With `color` disabled every method returns plain text without ANSI codes
+    pub fn with_theme(theme: Theme, color: bool) -> Self {
+        let scope = |name| Scope::new(name).expect("valid scope");
+        let scope_rules = SCOPE_RULES
+            .iter()
+            .map(|(name, category)| (scope(name), *category))
+            .collect();
         Self {
             syntax_set: SyntaxSet::load_defaults_newlines(),
-            theme_set: ThemeSet::load_defaults(),
+            scope_rules,
+            decorator_scope: scope(DECORATOR_SCOPE),
+            decorator_argument_scopes: DECORATOR_ARGUMENT_SCOPES
+                .iter()
+                .map(|name| scope(name))
+                .collect(),
+            theme,
+            depth: ColorDepth::default(),
+            color,
+            show_whitespace: false,
+            hyperlinks: false,
+            emphasized_words: Vec::new(),
         }
     }
 
-    // Creates a default style with white text on black background
-    fn create_default_style() -> Style {
-        Style {
-            foreground: Color::WHITE,
-            background: Color::BLACK,
-            font_style: syntect::highlighting::FontStyle::empty(),
-        }
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.depth = depth;
+    }
+
+    // Draw leading spaces and tabs as faint `·` and `→`, with or without color
+    pub fn set_show_whitespace(&mut self, show_whitespace: bool) {
+        self.show_whitespace = show_whitespace;
     }
 
-    // Checks if a text segment contains escape characters
-    fn contains_escape_chars(text: &str) -> bool {
-        text.contains('\\')
+    // Make the (always underlined) URLs in comments and strings clickable
+    // with OSC 8 escapes, for terminals that support them
+    pub fn set_hyperlinks(&mut self, hyperlinks: bool) {
+        self.hyperlinks = hyperlinks;
+    }
+
+    // Show these words in the emphasis style wherever they appear as whole
+    // words in code, outside strings and comments
+    pub fn set_emphasized_words(&mut self, words: Vec<String>) {
+        self.emphasized_words = words;
+    }
+
+    fn categorize(&self, stack: &ScopeStack) -> TokenCategory {
+        let scopes = stack.as_slice();
+        // A decorator's name wins over whatever its parts are tagged as, so
+        // `@staticmethod` doesn't read as a call to the builtin
+        let has_scope = |prefixes: &[Scope]| {
+            scopes
+                .iter()
+                .any(|scope| prefixes.iter().any(|prefix| prefix.is_prefix_of(*scope)))
+        };
+        if has_scope(&[self.decorator_scope]) && !has_scope(&self.decorator_argument_scopes) {
+            return TokenCategory::Decorator;
+        }
+
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| {
+                self.scope_rules
+                    .iter()
+                    .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+                    .map(|(_, category)| *category)
+            })
+            .unwrap_or(TokenCategory::Plain)
     }
 
     pub fn highlight(&self, code: &str) -> String {
+        self.highlight_marked(code, &[])
+    }
+
+    // Highlights `code` and additionally shows every case-insensitive
+    // occurrence of `term` in reverse video
+    pub fn highlight_with_match(&self, code: &str, term: &str) -> String {
+        self.highlight_marked(code, &find_matches(code, term))
+    }
+
+    // Highlights `code` with the byte ranges in `marks` in reverse video
+    pub(crate) fn highlight_marked(&self, code: &str, marks: &[Range<usize>]) -> String {
+        if !self.color {
+            return self.uncolored(code);
+        }
+        self.paint(code, &self.spans(code), marks)
+    }
+
+    // Highlights Python read from `input` a line at a time, writing each line
+    // out as soon as it's colored, so input of any size streams through
+    pub fn highlight_stream(
+        &self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> io::Result<()> {
+        let mut state = self.line_state();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return output.flush();
+            }
+            if self.color {
+                let mut spans = Vec::new();
+                self.push_line(&mut state, &line, 0..line.len(), &mut spans);
+                output.write_all(self.paint(&line, &spans, &[]).as_bytes())?;
+            } else {
+                output.write_all(self.uncolored(&line).as_bytes())?;
+            }
+        }
+    }
+
+    fn uncolored(&self, code: &str) -> String {
+        if !self.show_whitespace {
+            return code.to_string();
+        }
+        code.split_inclusive('\n')
+            .map(|line| {
+                let indent = indent_len(line);
+                format!("{}{}", visible_whitespace(&line[..indent]), &line[indent..])
+            })
+            .collect()
+    }
+
+    fn paint(
+        &self,
+        code: &str,
+        spans: &[(Range<usize>, TokenKind)],
+        marks: &[Range<usize>],
+    ) -> String {
+        let mut result = String::new();
+        // Whether the spans so far on this line were all indentation
+        let mut at_line_start = true;
+        for (range, span) in spans {
+            if let TokenKind::Unparsed = span {
+                result.push_str(&code[range.clone()]);
+                at_line_start = true;
+                continue;
+            }
+            let style = self.style(*span);
+            let text = &code[range.clone()];
+            let indent = if self.show_whitespace && at_line_start {
+                indent_len(text)
+            } else {
+                0
+            };
+            at_line_start = text.ends_with('\n') || indent == text.len();
+
+            if indent > 0 {
+                let whitespace = self.theme.whitespace();
+                Self::push_marked(&mut result, &text[..indent], range.start, marks, |text| {
+                    whitespace.paint(&visible_whitespace(text), self.depth)
+                });
+            }
+            let text = &text[indent..];
+            let start = range.start + indent;
+            let urls = match span {
+                TokenKind::Code(TokenCategory::Comment | TokenCategory::String) => find_urls(text),
+                _ => Vec::new(),
+            };
+            let mut pos = 0;
+            for url in urls {
+                Self::push_marked(
+                    &mut result,
+                    &text[pos..url.start],
+                    start + pos,
+                    marks,
+                    |text| style.paint(text, self.depth),
+                );
+                self.push_url(
+                    &mut result,
+                    &text[url.clone()],
+                    start + url.start,
+                    marks,
+                    style,
+                );
+                pos = url.end;
+            }
+            Self::push_marked(&mut result, &text[pos..], start + pos, marks, |text| {
+                style.paint(text, self.depth)
+            });
+        }
+        result
+    }
+
+    // A URL at byte `offset` of the input, underlined in its token's style and
+    // wrapped in a hyperlink if those are enabled
+    fn push_url(
+        &self,
+        result: &mut String,
+        url: &str,
+        offset: usize,
+        marks: &[Range<usize>],
+        style: TokenStyle,
+    ) {
+        if self.hyperlinks {
+            result.push_str(&format!("{}{}\x1b\\", HYPERLINK_OPEN, url));
+        }
+        let underlined = style.underlined();
+        Self::push_marked(result, url, offset, marks, |text| {
+            underlined.paint(text, self.depth)
+        });
+        if self.hyperlinks {
+            result.push_str(HYPERLINK_CLOSE);
+        }
+    }
+
+    // Highlights `code` as HTML: escaped text with each colored run in a
+    // `<span style="...">`. Independent of whether terminal color is enabled.
+    pub fn highlight_html(&self, code: &str) -> String {
+        let mut result = String::new();
+        for token in self.tokenize(code) {
+            let text = html::escape(&token.text);
+            match self.style(token.kind).css() {
+                Some(css) => result.push_str(&format!("<span style=\"{}\">{}</span>", css, text)),
+                None => result.push_str(&text),
+            }
+        }
+        result
+    }
+
+    // `code` split into the runs highlighting colors alike, in order, so
+    // joining their text gives `code` back
+    pub fn tokenize(&self, code: &str) -> Vec<Token> {
+        self.spans(code)
+            .into_iter()
+            .map(|(range, kind)| Token {
+                kind,
+                text: code[range].to_string(),
+            })
+            .collect()
+    }
+
+    // How the theme draws a kind of token
+    fn style(&self, kind: TokenKind) -> TokenStyle {
+        match kind {
+            TokenKind::Header => self.theme.header(),
+            TokenKind::Code(category) => self.theme.style(category),
+            TokenKind::Unparsed | TokenKind::Foreign => TokenStyle::PLAIN,
+        }
+    }
+
+    // Splits `code` into byte ranges to be drawn alike: header lines and
+    // fenced non-Python lines whole, Python source as runs of neighbouring
+    // tokens in the same category
+    fn spans(&self, code: &str) -> Vec<(Range<usize>, TokenKind)> {
+        let mut state = self.line_state();
+        let mut spans = Vec::new();
+        let mut offset = 0;
+        for line in LinesWithEndings::from(code) {
+            let line_range = offset..offset + line.len();
+            offset += line.len();
+            self.push_line(&mut state, line, line_range, &mut spans);
+        }
+        spans
+    }
+
+    fn line_state(&self) -> LineState {
         let syntax = self
             .syntax_set
             .find_syntax_by_extension("py")
             .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        LineState {
+            parse_state: ParseState::new(syntax),
+            stack: ScopeStack::new(),
+            in_fence: false,
+        }
+    }
 
-        let theme = &self.theme_set.themes["Solarized (dark)"];
-        let mut highlighter = HighlightLines::new(syntax, theme);
-        let mut result = String::new();
+    // Appends the spans of one line, `line_range` being where it sits in the
+    // input
+    fn push_line(
+        &self,
+        state: &mut LineState,
+        line: &str,
+        line_range: Range<usize>,
+        spans: &mut Vec<(Range<usize>, TokenKind)>,
+    ) {
+        let LineState {
+            parse_state,
+            stack,
+            in_fence,
+        } = state;
 
-        for line in LinesWithEndings::from(code) {
-            // Special handling for comment headers
-            if line.trim_start().starts_with("# ----")
-                || (line.trim_start().starts_with("# ") && line.contains(". "))
-            {
-                result.push_str(&self.format_header(line, false));
-                continue;
+        // Inside a triple-quoted string every line is string text, even
+        // one that looks like a header or fence
+        if self.categorize(stack) == TokenCategory::String {
+            self.push_tokens(parse_state, stack, line, line_range, spans);
+            return;
+        }
+
+        // Fenced lines skip the parser entirely, so shell quoting and the
+        // like can't leave it inside a string for the Python that follows
+        match fence_marker(line) {
+            Some(language) if !*in_fence && is_foreign(language) => {
+                *in_fence = true;
+                spans.push((line_range, TokenKind::Code(TokenCategory::Comment)));
+                return;
+            }
+            Some("") if *in_fence => {
+                *in_fence = false;
+                spans.push((line_range, TokenKind::Code(TokenCategory::Comment)));
+                return;
+            }
+            _ if *in_fence => {
+                spans.push((line_range, TokenKind::Foreign));
+                return;
             }
+            _ => {}
+        }
+
+        // Special handling for comment headers
+        if line.trim_start().starts_with("# ----")
+            || (line.trim_start().starts_with("# ") && line.contains(". "))
+        {
+            spans.push((line_range, TokenKind::Header));
+            return;
+        }
+
+        self.push_tokens(parse_state, stack, line, line_range, spans);
+    }
 
-            match highlighter.highlight_line(line, &self.syntax_set) {
-                Ok(ranges) => {
-                    for (style, text) in ranges {
-                        if Self::contains_escape_chars(text) {
-                            // Use default style for escape characters
-                            let default_style = Self::create_default_style();
-                            result.push_str(&Self::style_to_ansi(default_style, text));
-                        } else {
-                            // Create a filtered style that only keeps the foreground color
-                            let filtered_style = Style {
-                                foreground: style.foreground,
-                                background: Color::BLACK,
-                                font_style: syntect::highlighting::FontStyle::empty(),
-                            };
-                            result.push_str(&Self::style_to_ansi(filtered_style, text));
-                        }
+    // Parses one line of Python, carrying string and bracket state over from
+    // the lines before it, and appends its token runs
+    fn push_tokens(
+        &self,
+        parse_state: &mut ParseState,
+        stack: &mut ScopeStack,
+        line: &str,
+        line_range: Range<usize>,
+        spans: &mut Vec<(Range<usize>, TokenKind)>,
+    ) {
+        let Ok(ops) = parse_state.parse_line(line, &self.syntax_set) else {
+            spans.push((line_range, TokenKind::Unparsed));
+            return;
+        };
+
+        let mut run: Option<(TokenCategory, usize)> = None;
+        let mut token_offset = line_range.start;
+        for (text, op) in ScopeRegionIterator::new(&ops, line) {
+            if stack.apply(op).is_err() || text.is_empty() {
+                continue;
+            }
+            let region_start = token_offset - line_range.start;
+            for (category, len) in self.emphasize(line, region_start, text.len(), stack) {
+                match run {
+                    Some((current, _)) if current == category => {}
+                    Some((current, start)) => {
+                        spans.push((start..token_offset, TokenKind::Code(current)));
+                        run = Some((category, token_offset));
                     }
+                    None => run = Some((category, token_offset)),
                 }
-                Err(_) => {
-                    result.push_str(line);
-                }
+                token_offset += len;
             }
         }
+        if let Some((category, start)) = run {
+            spans.push((start..token_offset, TokenKind::Code(category)));
+        }
+    }
 
-        result
+    // Categorizes the `len` bytes of `line` from `start`, splitting out the
+    // emphasized words among them as pieces of their own. A word only counts
+    // when the characters either side of it on the line aren't word characters,
+    // so `yield` doesn't match inside `yielding`.
+    fn emphasize(
+        &self,
+        line: &str,
+        start: usize,
+        len: usize,
+        stack: &ScopeStack,
+    ) -> Vec<(TokenCategory, usize)> {
+        let category = self.categorize(stack);
+        if self.emphasized_words.is_empty()
+            || matches!(category, TokenCategory::String | TokenCategory::Comment)
+        {
+            return vec![(category, len)];
+        }
+
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let text = &line[start..start + len];
+        let mut pieces = Vec::new();
+        let mut piece_start = 0;
+        let mut i = 0;
+        while i < text.len() {
+            let word = self.emphasized_words.iter().find(|word| {
+                !word.is_empty()
+                    && text[i..].starts_with(word.as_str())
+                    && !line[..start + i]
+                        .chars()
+                        .next_back()
+                        .is_some_and(is_word_char)
+                    && !line[start + i + word.len()..]
+                        .chars()
+                        .next()
+                        .is_some_and(is_word_char)
+            });
+            match word {
+                Some(word) => {
+                    if i > piece_start {
+                        pieces.push((category, i - piece_start));
+                    }
+                    pieces.push((TokenCategory::Emphasized, word.len()));
+                    i += word.len();
+                    piece_start = i;
+                }
+                None => i += text[i..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        if piece_start < text.len() {
+            pieces.push((category, text.len() - piece_start));
+        }
+        pieces
     }
 
-    // Convert a Style and text to ANSI escaped string
-    fn style_to_ansi(style: Style, text: &str) -> String {
-        format!(
-            "\x1b[38;5;{};{};{}m{}\x1b[0m",
-            style.foreground.r, style.foreground.g, style.foreground.b, text
-        )
+    // Renders a token starting at byte `offset` of the input, splitting it so
+    // that parts inside `marks` get the match emphasis. Each part is rendered
+    // (and reset) on its own, so emphasis never lands inside an escape sequence.
+    fn push_marked(
+        result: &mut String,
+        text: &str,
+        offset: usize,
+        marks: &[Range<usize>],
+        render: impl Fn(&str) -> String,
+    ) {
+        let end = offset + text.len();
+        let mut pos = offset;
+
+        for mark in marks
+            .iter()
+            .filter(|mark| mark.start < end && mark.end > offset)
+        {
+            let start = mark.start.max(pos);
+            let stop = mark.end.min(end);
+            if start > pos {
+                result.push_str(&render(&text[pos - offset..start - offset]));
+            }
+            let rendered = render(&text[start - offset..stop - offset]);
+            result.push_str(MATCH_EMPHASIS);
+            result.push_str(&rendered);
+            if !rendered.ends_with("\x1b[0m") {
+                result.push_str("\x1b[0m");
+            }
+            pos = stop;
+        }
+
+        if pos < end {
+            result.push_str(&render(&text[pos - offset..]));
+        }
     }
 
     pub fn format_header(&self, text: &str, is_title: bool) -> String {
-        if is_title {
-            format!("\x1b[36m{}\x1b[0m", text) // Cyan for titles
+        if !self.color {
+            text.to_string()
+        } else if is_title {
+            self.theme.title().paint(text, self.depth)
         } else {
-            format!("\x1b[34m{}\x1b[0m", text) // Blue for other headers
+            self.theme.header().paint(text, self.depth)
+        }
+    }
+
+    pub fn format_line_number(&self, number: &str) -> String {
+        if !self.color {
+            return number.to_string();
+        }
+        // Reset first so a color left open from the previous line doesn't bleed in
+        format!("\x1b[0m\x1b[2m{}\x1b[0m", number) // Dim for line numbers
+    }
+
+    pub fn format_warning(&self, warning: &str) -> String {
+        if !self.color {
+            return format!("Warning: {}", warning);
         }
+        format!("\x1b[33mWarning: {}\x1b[0m", warning) // Yellow for warnings
     }
 
     pub fn format_error(&self, error: &str) -> String {
+        if !self.color {
+            return format!("Error: {}", error);
+        }
         format!("\x1b[31mError: {}\x1b[0m", error) // Red for errors
     }
 }
 
 impl Default for PythonHighlighter {
     fn default() -> Self {
-        Self::new()
+        Self::new(Theme::default())
+    }
+}
+
+// Byte ranges of the `http://` and `https://` URLs in `text`. A URL ends at
+// whitespace or a quote, and trailing punctuation is left out, as is a closing
+// bracket the URL didn't open (so `(see https://a.b/c)` stops before `)`).
+fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let mut urls = Vec::new();
+    let mut from = 0;
+    while let Some(start) = URL_SCHEMES
+        .iter()
+        .filter_map(|scheme| text[from..].find(scheme).map(|i| from + i))
+        .min()
+    {
+        let length = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '`'))
+            .unwrap_or(text.len() - start);
+        let mut url = &text[start..start + length];
+        loop {
+            let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            let unopened = [('(', ')'), ('[', ']'), ('{', '}')]
+                .iter()
+                .any(|(open, close)| {
+                    trimmed.ends_with(*close)
+                        && trimmed.matches(*close).count() > trimmed.matches(*open).count()
+                });
+            let trimmed = if unopened {
+                &trimmed[..trimmed.len() - 1]
+            } else {
+                trimmed
+            };
+            if trimmed.len() == url.len() {
+                break;
+            }
+            url = trimmed;
+        }
+
+        if !URL_SCHEMES.contains(&url) {
+            urls.push(start..start + url.len());
+        }
+        from = start + length;
+    }
+    urls
+}
+
+// Length in bytes of the spaces and tabs starting `text`
+fn indent_len(text: &str) -> usize {
+    text.len() - text.trim_start_matches([' ', '\t']).len()
+}
+
+fn visible_whitespace(indent: &str) -> String {
+    indent.replace(' ', SPACE_GLYPH).replace('\t', TAB_GLYPH)
+}
+
+// Byte ranges of non-overlapping, case-insensitive occurrences of `term`
+pub(crate) fn find_matches(haystack: &str, term: &str) -> Vec<Range<usize>> {
+    let term: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+    if term.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    let mut search_from = 0;
+    for (start, _) in haystack.char_indices() {
+        if start < search_from {
+            continue;
+        }
+        if let Some(len) = match_len(&haystack[start..], &term) {
+            matches.push(start..start + len);
+            search_from = start + len;
+        }
+    }
+    matches
+}
+
+// Byte length of the prefix of `text` equal to the lowercased `term`, if any
+fn match_len(text: &str, term: &[char]) -> Option<usize> {
+    let mut remaining = term;
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            match remaining.split_first() {
+                Some((want, rest)) if *want == lower => remaining = rest,
+                _ => return None,
+            }
+        }
+        if remaining.is_empty() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The category of the token that is exactly `text`, ignoring surrounding
+    // whitespace
+    fn category_of(code: &str, text: &str) -> Option<TokenCategory> {
+        PythonHighlighter::with_color(false)
+            .tokenize(code)
+            .into_iter()
+            .find(|token| token.text.trim() == text)
+            .and_then(|token| match token.kind {
+                TokenKind::Code(category) => Some(category),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn every_line_of_a_docstring_is_a_string() {
+        let code =
+            "def f():\n    \"\"\"First line.\n\n    Second line.\n    \"\"\"\n    return 1\n";
+        let tokens = PythonHighlighter::with_color(false).tokenize(code);
+        let strings: String = tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Code(TokenCategory::String))
+            .map(|token| token.text.as_str())
+            .collect();
+
+        assert!(strings.contains("\"\"\"First line.\n\n    Second line.\n    \"\"\""));
+        assert_eq!(category_of(code, "return"), Some(TokenCategory::Keyword));
+    }
+
+    #[test]
+    fn triple_quotes_closed_on_one_line_end_the_string() {
+        for quotes in ["\"\"\"", "'''"] {
+            let string = format!("{}one{}", quotes, quotes);
+            let code = format!("x = {}\ny = 2\n", string);
+            assert_eq!(category_of(&code, &string), Some(TokenCategory::String));
+            assert_eq!(category_of(&code, "2"), Some(TokenCategory::Number));
+        }
+    }
+
+    #[test]
+    fn triple_single_quotes_span_lines() {
+        let code = "s = '''a\nb'''\nz = 3\n";
+        assert_eq!(category_of(code, "'''a"), Some(TokenCategory::String));
+        assert_eq!(category_of(code, "b'''"), Some(TokenCategory::String));
+        assert_eq!(category_of(code, "3"), Some(TokenCategory::Number));
+    }
+
+    #[test]
+    fn numeric_literals_are_numbers() {
+        for literal in [
+            "0xff",
+            "0b1010",
+            "0o17",
+            "1.5e-3",
+            "2e10",
+            "3j",
+            "1.5j",
+            "1_000_000",
+        ] {
+            let code = format!("x = {}\n", literal);
+            assert_eq!(
+                category_of(&code, literal),
+                Some(TokenCategory::Number),
+                "{}",
+                literal
+            );
+        }
+    }
+
+    #[test]
+    fn digits_in_a_name_are_not_a_number() {
+        let tokens = PythonHighlighter::with_color(false).tokenize("var1 = 2\n");
+        assert!(tokens
+            .iter()
+            .filter(|token| token.text.contains("var1"))
+            .all(|token| token.kind != TokenKind::Code(TokenCategory::Number)));
+        assert_eq!(category_of("var1 = 2\n", "2"), Some(TokenCategory::Number));
+    }
+
+    #[test]
+    fn decorator_at_line_start_is_a_decorator() {
+        let code = "@decorator\ndef f():\n    pass\n";
+        assert_eq!(
+            category_of(code, "@decorator"),
+            Some(TokenCategory::Decorator)
+        );
+    }
+
+    #[test]
+    fn dotted_decorator_stops_before_its_arguments() {
+        let code = "@app.route(\"/\")\ndef index():\n    pass\n";
+        let tokens = PythonHighlighter::with_color(false).tokenize(code);
+        assert_eq!(tokens[0].text, "@app.route");
+        assert_eq!(tokens[0].kind, TokenKind::Code(TokenCategory::Decorator));
+        assert!(tokens[1].text.starts_with('('));
+        assert_ne!(tokens[1].kind, TokenKind::Code(TokenCategory::Decorator));
+    }
+
+    #[test]
+    fn matrix_multiplication_is_an_operator() {
+        let tokens = PythonHighlighter::with_color(false).tokenize("c = a @ b\n");
+        let at = tokens
+            .iter()
+            .find(|token| token.text.contains('@'))
+            .unwrap();
+        assert_eq!(at.kind, TokenKind::Code(TokenCategory::Plain));
     }
 }
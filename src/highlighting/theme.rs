@@ -0,0 +1,306 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+// What a piece of Python source is, as far as coloring goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCategory {
+    Keyword,
+    String,
+    Comment,
+    Constant,
+    // Integer, float and complex literals in any base
+    Number,
+    Function,
+    // Built-in functions and types such as `len` or `list`
+    Builtin,
+    Class,
+    // The braces around an f-string's embedded expression
+    Interpolation,
+    // A decorator's `@name` or `@a.b`, up to any arguments
+    Decorator,
+    // A word picked out with `--highlight-keyword`
+    Emphasized,
+    Plain,
+}
+
+// How many colors the terminal can show. True color uses a theme's RGB values
+// directly; otherwise each color falls back to its 16-color ANSI equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    #[default]
+    Ansi16,
+    TrueColor,
+}
+
+impl ColorDepth {
+    // True color when COLORTERM advertises it, as most 24-bit terminals do
+    pub fn detect() -> Self {
+        match env::var("COLORTERM").as_deref() {
+            Ok("truecolor") | Ok("24bit") => ColorDepth::TrueColor,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+}
+
+// A theme color: its RGB value plus the SGR foreground code (30-37, 90-97)
+// closest to it on a 16-color terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub ansi: u8,
+    pub rgb: (u8, u8, u8),
+}
+
+impl Color {
+    const fn new(ansi: u8, rgb: u32) -> Self {
+        Self {
+            ansi,
+            rgb: ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+        }
+    }
+
+    fn sgr(&self, depth: ColorDepth) -> String {
+        match depth {
+            ColorDepth::Ansi16 => self.ansi.to_string(),
+            ColorDepth::TrueColor => {
+                let (r, g, b) = self.rgb;
+                format!("38;2;{};{};{}", r, g, b)
+            }
+        }
+    }
+}
+
+// How one category is drawn: a foreground color plus optional bold, dim or
+// underline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenStyle {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+}
+
+impl TokenStyle {
+    pub const PLAIN: Self = Self::new(None);
+    const BOLD: Self = Self::new(None).bold();
+    const DIM: Self = Self::new(None).dim();
+
+    const fn new(color: Option<Color>) -> Self {
+        Self {
+            color,
+            bold: false,
+            dim: false,
+            underline: false,
+        }
+    }
+
+    const fn fg(ansi: u8, rgb: u32) -> Self {
+        Self::new(Some(Color::new(ansi, rgb)))
+    }
+
+    const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    const fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    pub const fn underlined(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    // `text` wrapped in this style's escape codes; plain text when the style
+    // sets nothing
+    pub fn paint(&self, text: &str, depth: ColorDepth) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.dim {
+            codes.push("2".to_string());
+        }
+        if self.underline {
+            codes.push("4".to_string());
+        }
+        if let Some(color) = self.color {
+            codes.push(color.sgr(depth));
+        }
+
+        if codes.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+        }
+    }
+
+    // Inline CSS for this style, or None when it sets nothing
+    pub fn css(&self) -> Option<String> {
+        let mut rules = Vec::new();
+        if let Some(color) = self.color {
+            let (r, g, b) = color.rgb;
+            rules.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.bold {
+            rules.push("font-weight:bold".to_string());
+        }
+        if self.dim {
+            rules.push("opacity:0.6".to_string());
+        }
+        if self.underline {
+            rules.push("text-decoration:underline".to_string());
+        }
+        (!rules.is_empty()).then(|| rules.join(";"))
+    }
+}
+
+// The full set of styles a theme assigns: one per token category, plus the
+// sheet titles and `# ----` / `# N. Title` header lines
+struct Palette {
+    keyword: TokenStyle,
+    string: TokenStyle,
+    comment: TokenStyle,
+    constant: TokenStyle,
+    number: TokenStyle,
+    function: TokenStyle,
+    builtin: TokenStyle,
+    class: TokenStyle,
+    interpolation: TokenStyle,
+    decorator: TokenStyle,
+    emphasized: TokenStyle,
+    title: TokenStyle,
+    header: TokenStyle,
+}
+
+// One Dark's accents
+const DEFAULT: Palette = Palette {
+    keyword: TokenStyle::fg(35, 0xc678dd),  // Magenta
+    string: TokenStyle::fg(32, 0x98c379),   // Green
+    comment: TokenStyle::fg(90, 0x7f848e),  // Bright black
+    constant: TokenStyle::fg(33, 0xd19a66), // Yellow
+    number: TokenStyle::fg(36, 0x56b6c2),   // Cyan
+    function: TokenStyle::fg(34, 0x61afef), // Blue
+    builtin: TokenStyle::fg(96, 0x2bbac5),  // Bright cyan
+    class: TokenStyle::fg(33, 0xe5c07b).bold(),
+    interpolation: TokenStyle::fg(31, 0xe06c75), // Red
+    decorator: TokenStyle::fg(93, 0xd7ba7d),     // Bright yellow
+    emphasized: TokenStyle::fg(91, 0xef596f).bold(), // Bright red
+    title: TokenStyle::fg(36, 0x56b6c2),         // Cyan
+    header: TokenStyle::fg(34, 0x61afef),
+};
+
+// Solarized's accents, falling back to the slots a Solarized terminal palette
+// puts them in
+const SOLARIZED: Palette = Palette {
+    keyword: TokenStyle::fg(32, 0x859900),       // Green
+    string: TokenStyle::fg(36, 0x2aa198),        // Cyan
+    comment: TokenStyle::fg(92, 0x586e75),       // base01
+    constant: TokenStyle::fg(35, 0xd33682),      // Magenta
+    number: TokenStyle::fg(95, 0x6c71c4),        // Violet
+    function: TokenStyle::fg(34, 0x268bd2),      // Blue
+    builtin: TokenStyle::fg(31, 0xdc322f),       // Red
+    class: TokenStyle::fg(33, 0xb58900),         // Yellow
+    interpolation: TokenStyle::fg(91, 0xcb4b16), // Orange
+    decorator: TokenStyle::fg(91, 0xcb4b16),
+    emphasized: TokenStyle::fg(31, 0xdc322f).bold(),
+    title: TokenStyle::fg(36, 0x2aa198),
+    header: TokenStyle::fg(34, 0x268bd2),
+};
+
+// Bold and dim only, for terminals with unreadable color palettes
+const MONOCHROME: Palette = Palette {
+    keyword: TokenStyle::BOLD,
+    string: TokenStyle::PLAIN,
+    comment: TokenStyle::DIM,
+    constant: TokenStyle::PLAIN,
+    number: TokenStyle::PLAIN,
+    function: TokenStyle::BOLD,
+    builtin: TokenStyle::PLAIN,
+    class: TokenStyle::BOLD,
+    interpolation: TokenStyle::BOLD,
+    decorator: TokenStyle::BOLD,
+    emphasized: TokenStyle::BOLD.underlined(),
+    title: TokenStyle::BOLD,
+    header: TokenStyle::BOLD,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Default,
+    Solarized,
+    Monochrome,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 3] = ["default", "solarized", "monochrome"];
+
+    fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Default => &DEFAULT,
+            Theme::Solarized => &SOLARIZED,
+            Theme::Monochrome => &MONOCHROME,
+        }
+    }
+
+    pub fn style(self, category: TokenCategory) -> TokenStyle {
+        let palette = self.palette();
+        match category {
+            TokenCategory::Keyword => palette.keyword,
+            TokenCategory::String => palette.string,
+            TokenCategory::Comment => palette.comment,
+            TokenCategory::Constant => palette.constant,
+            TokenCategory::Number => palette.number,
+            TokenCategory::Function => palette.function,
+            TokenCategory::Builtin => palette.builtin,
+            TokenCategory::Class => palette.class,
+            TokenCategory::Interpolation => palette.interpolation,
+            TokenCategory::Decorator => palette.decorator,
+            TokenCategory::Emphasized => palette.emphasized,
+            TokenCategory::Plain => TokenStyle::PLAIN,
+        }
+    }
+
+    pub fn title(self) -> TokenStyle {
+        self.palette().title
+    }
+
+    pub fn header(self) -> TokenStyle {
+        self.palette().header
+    }
+
+    // Indentation glyphs are faint in every theme
+    pub fn whitespace(self) -> TokenStyle {
+        TokenStyle::DIM
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(Theme::Default),
+            "solarized" => Ok(Theme::Solarized),
+            "monochrome" | "mono" => Ok(Theme::Monochrome),
+            _ => Err(format!(
+                "Unknown theme '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Default => write!(f, "default"),
+            Theme::Solarized => write!(f, "solarized"),
+            Theme::Monochrome => write!(f, "monochrome"),
+        }
+    }
+}
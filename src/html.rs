@@ -0,0 +1,95 @@
+use crate::cheatsheet::Section;
+use crate::highlighting::PythonHighlighter;
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }\n\
+pre { background: #282c34; color: #abb2bf; padding: 1em; overflow-x: auto; }";
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Anchor id for a section, unique across sheets on the same page
+pub(crate) fn anchor(sheet_name: &str, number: usize) -> String {
+    let slug: String = sheet_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}-{}", slug, number)
+}
+
+// Wraps rendered sections in a standalone page
+pub fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+// Renders a section as an anchored `<h2>` followed by its highlighted body
+pub fn section(
+    sheet_name: &str,
+    number: usize,
+    section: &Section,
+    highlighter: &PythonHighlighter,
+) -> String {
+    format!(
+        "<h2 id=\"{}\">{}. {}</h2>\n<pre><code>{}</code></pre>\n",
+        anchor(sheet_name, number),
+        number,
+        escape(&section.title),
+        highlighter.highlight_html(section.body().trim_matches('\n'))
+    )
+}
+
+// Renders a sheet's section titles as a numbered list under its name, linking
+// to the sections when they're on the same page
+fn contents(sheet_name: &str, sections: &[Section], linked: bool) -> String {
+    let mut output = format!("<h1>{}</h1>\n<ol>\n", escape(sheet_name));
+    for (i, section) in sections.iter().enumerate() {
+        let title = escape(&section.title);
+        if linked {
+            output.push_str(&format!(
+                "<li><a href=\"#{}\">{}</a></li>\n",
+                anchor(sheet_name, i + 1),
+                title
+            ));
+        } else {
+            output.push_str(&format!("<li>{}</li>\n", title));
+        }
+    }
+    output.push_str("</ol>\n");
+    output
+}
+
+pub fn outline(sheet_name: &str, sections: &[Section]) -> String {
+    contents(sheet_name, sections, false)
+}
+
+// Renders every section of a sheet after a linked table of contents
+pub fn sheet(sheet_name: &str, sections: &[Section], highlighter: &PythonHighlighter) -> String {
+    let mut output = contents(sheet_name, sections, true);
+    for (i, item) in sections.iter().enumerate() {
+        output.push_str(&section(sheet_name, i + 1, item, highlighter));
+    }
+    output
+}
@@ -0,0 +1,99 @@
+use crate::cheatsheet::Section;
+use std::collections::{BTreeMap, BTreeSet};
+
+// (sheet position, section position) within the manager's sheets
+pub type Location = (usize, usize);
+
+// Inverted index from lowercase words to the sections containing them, so a
+// search only needs to check sections that can possibly match. The words are
+// also kept reversed, so words ending in something can be found in order too.
+pub struct SearchIndex {
+    postings: BTreeMap<String, BTreeSet<Location>>,
+    reversed: BTreeSet<String>,
+}
+
+// Runs of letters, digits and underscores, as in Python identifiers
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+}
+
+fn reverse(word: &str) -> String {
+    word.chars().rev().collect()
+}
+
+impl SearchIndex {
+    pub fn build<'a>(sheets: impl IntoIterator<Item = &'a [Section]>) -> Self {
+        let mut postings: BTreeMap<String, BTreeSet<Location>> = BTreeMap::new();
+        for (sheet, sections) in sheets.into_iter().enumerate() {
+            for (section, item) in sections.iter().enumerate() {
+                let text = format!("{}\n{}", item.title, item.content).to_lowercase();
+                for word in words(&text) {
+                    postings
+                        .entry(word.to_string())
+                        .or_default()
+                        .insert((sheet, section));
+                }
+            }
+        }
+        let reversed = postings.keys().map(|word| reverse(word)).collect();
+        Self { postings, reversed }
+    }
+
+    // Sections that may contain the lowercase `needle`; the caller still
+    // checks each one, as the index knows nothing of word order. A word with
+    // non-word characters on both sides in the needle must be an indexed word,
+    // one that opens the needle may end a longer one and one that closes it
+    // may start a longer one, each an ordered lookup. Only a needle that is a
+    // single word can sit in the middle of an indexed word, which takes a scan
+    // of every word. None when the needle has no words to look up, so every
+    // section is a candidate.
+    pub fn candidates(&self, needle: &str) -> Option<BTreeSet<Location>> {
+        let needle_words: Vec<&str> = words(needle).collect();
+        let mut candidates: Option<BTreeSet<Location>> = None;
+        for (i, &word) in needle_words.iter().enumerate() {
+            let starts_word = i > 0 || !needle.starts_with(word);
+            let ends_word = i + 1 < needle_words.len() || !needle.ends_with(word);
+            let matching: BTreeSet<Location> = self
+                .matching_words(word, starts_word, ends_word)
+                .into_iter()
+                .filter_map(|indexed| self.postings.get(&indexed))
+                .flat_map(|locations| locations.iter().copied())
+                .collect();
+            candidates = Some(match candidates {
+                Some(found) => found.intersection(&matching).copied().collect(),
+                None => matching,
+            });
+        }
+        candidates
+    }
+
+    // Indexed words that can hold `word`, given whether it must start and
+    // end one
+    fn matching_words(&self, word: &str, starts_word: bool, ends_word: bool) -> Vec<String> {
+        match (starts_word, ends_word) {
+            (true, true) => vec![word.to_string()],
+            (true, false) => self
+                .postings
+                .range(word.to_string()..)
+                .map(|(indexed, _)| indexed)
+                .take_while(|indexed| indexed.starts_with(word))
+                .cloned()
+                .collect(),
+            (false, true) => {
+                let reversed_word = reverse(word);
+                self.reversed
+                    .range(reversed_word.clone()..)
+                    .take_while(|reversed| reversed.starts_with(&reversed_word))
+                    .map(|reversed| reverse(reversed))
+                    .collect()
+            }
+            (false, false) => self
+                .postings
+                .keys()
+                .filter(|indexed| indexed.contains(word))
+                .cloned()
+                .collect(),
+        }
+    }
+}
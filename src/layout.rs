@@ -0,0 +1,375 @@
+use std::io::{self, IsTerminal};
+use terminal_size::{terminal_size, Width};
+use unicode_width::UnicodeWidthChar;
+
+pub(crate) const DEFAULT_WIDTH: usize = 80;
+const MIN_ROW_WIDTH: usize = 20;
+const CONTINUATION_MARKER: &str = "↪";
+const COLUMN_GAP: usize = 4;
+
+// Terminal width in columns, or 80 when stdout isn't a terminal
+pub fn terminal_width() -> usize {
+    if !io::stdout().is_terminal() {
+        return DEFAULT_WIDTH;
+    }
+    terminal_size().map_or(DEFAULT_WIDTH, |(Width(width), _)| width as usize)
+}
+
+// Length in bytes of the escape sequence at the start of `text`: CSI
+// (`ESC [ ... final`) or OSC (`ESC ] ... BEL` / `ESC ] ... ESC \`)
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        Some(b']') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+// Removes ANSI escape sequences so only printable text remains
+pub fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(esc) = rest.find('\x1b') {
+        result.push_str(&rest[..esc]);
+        rest = &rest[esc + escape_len(&rest[esc..])..];
+    }
+    result.push_str(rest);
+    result
+}
+
+// Cuts a (possibly highlighted) line down to `width` terminal columns,
+// keeping every escape sequence so colors still reset properly afterwards. A
+// wide char that would straddle the edge is dropped with everything after it.
+pub fn truncate(line: &str, width: usize) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut visible = 0;
+    let mut cut = false;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' {
+            let len = escape_len(rest);
+            result.push_str(&rest[..len]);
+            len
+        } else {
+            cut |= visible + char_width(c) > width;
+            if !cut {
+                result.push(c);
+                visible += char_width(c);
+            }
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    result
+}
+
+// Columns `c` takes up in a terminal, by its Unicode East Asian Width: none
+// for combining marks and other zero-width chars, two for wide chars and
+// emoji, else one. Control chars such as tabs count as one.
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(1)
+}
+
+// Cuts plain `text` down to `width` terminal columns, ending it in `…` when
+// anything was cut. Counts display width, so wide chars take two columns.
+pub fn ellipsize(text: &str, width: usize) -> String {
+    if text.chars().map(char_width).sum::<usize>() <= width {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut used = 0;
+    for c in text.chars() {
+        let c_width = char_width(c);
+        if used + c_width >= width {
+            break;
+        }
+        result.push(c);
+        used += c_width;
+    }
+    result.push('…');
+    result
+}
+
+// Visible char positions where each continuation row starts. Rows break after
+// the last space that fits, or mid-word when a row has no space.
+fn break_points(chars: &[char], width: usize, continuation_width: usize) -> Vec<usize> {
+    let mut breaks = Vec::new();
+    let mut start = 0;
+    let mut available = width.max(MIN_ROW_WIDTH);
+
+    while chars.len() - start > available {
+        let limit = start + available;
+        let has_content = |end: usize| chars[start..end].iter().any(|c| !c.is_whitespace());
+        let word_break = (start + 1..limit)
+            .rev()
+            .find(|&i| chars[i - 1] == ' ' && chars[i] != ' ' && has_content(i - 1));
+
+        start = word_break.unwrap_or(limit);
+        breaks.push(start);
+        available = width.saturating_sub(continuation_width).max(MIN_ROW_WIDTH);
+    }
+
+    breaks
+}
+
+// Splits one (possibly highlighted) line into rows no wider than `width`.
+// Continuation rows keep the line's indentation; comments continue with `# `
+// and code with a marker so wrapped Python isn't mistaken for a new line.
+// Colors active at a break are reset before it and restored after it.
+pub fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let visible: Vec<char> = strip_ansi(line).chars().collect();
+    if visible.len() <= width {
+        return vec![line.to_string()];
+    }
+
+    let indent: String = visible.iter().take_while(|c| c.is_whitespace()).collect();
+    let is_comment = visible[indent.chars().count()..].first() == Some(&'#');
+    let continuation = if is_comment {
+        format!("{}# ", indent)
+    } else {
+        format!("{}{} ", indent, CONTINUATION_MARKER)
+    };
+    let breaks = break_points(&visible, width, continuation.chars().count());
+
+    let mut rows = Vec::with_capacity(breaks.len() + 1);
+    let mut row = String::new();
+    let mut active: Vec<&str> = Vec::new();
+    let mut next_break = breaks.iter().peekable();
+    let mut position = 0;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let sequence = &rest[..escape_len(rest)];
+            if sequence.starts_with("\x1b[") && sequence.ends_with('m') {
+                if sequence == "\x1b[0m" || sequence == "\x1b[m" {
+                    active.clear();
+                } else {
+                    active.push(sequence);
+                }
+            }
+            row.push_str(sequence);
+            rest = &rest[sequence.len()..];
+            continue;
+        }
+
+        if next_break.peek() == Some(&&position) {
+            next_break.next();
+            if !active.is_empty() {
+                row.push_str("\x1b[0m");
+            }
+            rows.push(std::mem::take(&mut row));
+            row.extend(active.iter().copied());
+            row.push_str(&continuation);
+        }
+
+        row.push(c);
+        position += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+
+    rows.push(row);
+    rows
+}
+
+// Lays out (possibly highlighted) text line by line: wraps lines wider than
+// `wrap_width` and prefixes each line with a right-aligned number, leaving the
+// gutter blank on continuation rows. A trailing fragment holding only escape
+// codes (the highlighter's final reset) is passed through untouched.
+pub fn layout_lines(
+    text: &str,
+    wrap_width: Option<usize>,
+    format_number: Option<&dyn Fn(&str) -> String>,
+) -> String {
+    let mut lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let trailer = match lines.last() {
+        Some(last) if !last.ends_with('\n') && strip_ansi(last).is_empty() => lines.pop(),
+        _ => None,
+    };
+
+    let number_width = lines.len().to_string().len();
+    let gutter_width = if format_number.is_some() {
+        number_width + 3
+    } else {
+        0
+    };
+
+    let mut result = String::with_capacity(text.len());
+    for (i, line) in lines.iter().enumerate() {
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (*line, ""),
+        };
+        let rows = match wrap_width {
+            Some(width) => wrap_line(content, width.saturating_sub(gutter_width)),
+            None => vec![content.to_string()],
+        };
+
+        for (j, row) in rows.iter().enumerate() {
+            if let Some(format_number) = format_number {
+                let gutter = if j == 0 {
+                    format!("{:>number_width$} │ ", i + 1)
+                } else {
+                    format!("{:>number_width$} │ ", "")
+                };
+                result.push_str(&format_number(&gutter));
+            }
+            result.push_str(row);
+            result.push_str(if j + 1 < rows.len() { "\n" } else { newline });
+        }
+    }
+    if let Some(trailer) = trailer {
+        result.push_str(trailer);
+    }
+    result
+}
+
+// Terminal columns a (possibly highlighted) line takes up
+fn visible_width(line: &str) -> usize {
+    strip_ansi(line).chars().map(char_width).sum()
+}
+
+fn block_width(block: &str) -> usize {
+    block.lines().map(visible_width).max().unwrap_or(0)
+}
+
+// (Possibly highlighted) `text` inside a rounded box at most `max_width`
+// columns wide, with `title` in its top border. The box fits the widest line;
+// lines too wide for it are cut, and leading and trailing blank lines dropped.
+pub fn boxed(
+    text: &str,
+    title: &str,
+    max_width: usize,
+    format_border: &dyn Fn(&str) -> String,
+    format_title: &dyn Fn(&str) -> String,
+) -> String {
+    let mut lines: Vec<&str> = text
+        .lines()
+        .skip_while(|line| strip_ansi(line).trim().is_empty())
+        .collect();
+    while lines
+        .last()
+        .is_some_and(|line| strip_ansi(line).trim().is_empty())
+    {
+        lines.pop();
+    }
+
+    let limit = max_width.saturating_sub(4).max(MIN_ROW_WIDTH);
+    let title = ellipsize(title, limit - 1);
+    let title_width = visible_width(&title);
+    let inner = lines
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0)
+        .max(title_width + 1)
+        .min(limit);
+
+    let mut result = format_border("╭─ ");
+    result.push_str(&format_title(&title));
+    result.push_str(&format_border(&format!(
+        " {}╮",
+        "─".repeat(inner - title_width - 1)
+    )));
+    result.push('\n');
+    for line in lines {
+        let line = truncate(line, inner);
+        result.push_str(&format_border("│ "));
+        result.push_str(&line);
+        result.push_str(&" ".repeat(inner - visible_width(&line)));
+        result.push_str(&format_border(" │"));
+        result.push('\n');
+    }
+    result.push_str(&format_border(&format!("╰{}╯", "─".repeat(inner + 2))));
+    result.push('\n');
+    result
+}
+
+// How many blocks fit side by side in `width` columns when every column is as
+// wide as the widest block; at least one
+pub fn column_count(blocks: &[String], width: usize) -> usize {
+    let column_width = blocks
+        .iter()
+        .map(|block| block_width(block))
+        .max()
+        .unwrap_or(0);
+    ((width + COLUMN_GAP) / (column_width + COLUMN_GAP)).clamp(1, blocks.len().max(1))
+}
+
+// Multi-line blocks laid out next to each other, each padded to the widest
+// block's width so the columns line up
+pub fn side_by_side(blocks: &[String]) -> String {
+    if let [block] = blocks {
+        return block.clone();
+    }
+
+    let column_width = blocks
+        .iter()
+        .map(|block| block_width(block))
+        .max()
+        .unwrap_or(0);
+    let columns: Vec<Vec<&str>> = blocks.iter().map(|block| block.lines().collect()).collect();
+    let height = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut result = String::new();
+    for row in 0..height {
+        let mut line = String::new();
+        for (i, column) in columns.iter().enumerate() {
+            let cell = column.get(row).copied().unwrap_or("");
+            line.push_str(cell);
+            if i + 1 < columns.len() {
+                let padding = column_width - visible_width(cell) + COLUMN_GAP;
+                line.push_str(&" ".repeat(padding));
+            }
+        }
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result
+}
+
+// The lines of (possibly highlighted) `text` at `matches`, each with up to
+// `context` lines either side, as in `grep -C`. Groups that don't touch are
+// separated by a `…` line. Every kept line is reset at its end, since a color
+// may otherwise run on from a line that was left out.
+pub fn excerpt(text: &str, matches: &[usize], context: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut keep = vec![false; lines.len()];
+    for &line in matches {
+        let start = line.saturating_sub(context);
+        let end = (line + context + 1).min(lines.len());
+        keep[start..end].iter_mut().for_each(|kept| *kept = true);
+    }
+
+    let mut result = String::new();
+    let mut previous = None;
+    for (i, line) in lines.iter().enumerate().filter(|(i, _)| keep[*i]) {
+        if previous.is_some_and(|previous| previous + 1 < i) {
+            result.push_str("…\n");
+        }
+        result.push_str(line);
+        if line.contains('\x1b') {
+            result.push_str("\x1b[0m");
+        }
+        result.push('\n');
+        previous = Some(i);
+    }
+    result
+}
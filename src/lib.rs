@@ -0,0 +1,27 @@
+pub mod cheatsheet;
+mod clipboard;
+mod diff;
+pub mod error;
+pub mod format;
+mod fuzzy;
+mod fzf;
+pub mod highlighting;
+mod html;
+mod index;
+mod layout;
+mod lint;
+pub mod manager;
+mod markdown;
+mod pager;
+mod quiz;
+mod references;
+mod selection;
+mod stats;
+mod tui;
+
+pub use cheatsheet::{CheatSheet, ParseOptions, Section};
+pub use error::CheatError;
+pub use format::OutputFormat;
+pub use highlighting::{ColorDepth, PythonHighlighter, Theme, Token, TokenCategory, TokenKind};
+pub use layout::terminal_width;
+pub use manager::{CheatSheetManager, BUILTIN_SHEETS};
@@ -0,0 +1,194 @@
+use crate::cheatsheet::Section;
+
+// Python counts a tab as advancing to the next multiple of 8 columns
+const TAB_STOP: usize = 8;
+
+// One problem found in a section; `line` counts lines of the section content
+// from 1, as `--line-numbers` shows them
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+}
+
+// Where a scan of the section stands between lines
+#[derive(Default)]
+struct Scan {
+    problems: Vec<Problem>,
+    // Open brackets and the lines they were opened on
+    brackets: Vec<(char, usize)>,
+    // An open triple-quoted string's delimiter and the line it began on
+    open_string: Option<(&'static str, usize)>,
+    indents: Vec<usize>,
+    // The last logical line ended in `:`, so a more indented block must follow
+    expect_indent: bool,
+    // The last line ended in a backslash, so this one continues it
+    continued: bool,
+}
+
+impl Scan {
+    fn report(&mut self, line: usize, message: impl Into<String>) {
+        self.problems.push(Problem {
+            line,
+            message: message.into(),
+        });
+    }
+
+    // Checks the indentation of a line that starts a logical line of code
+    fn check_indent(&mut self, line: usize, indent: &str) {
+        if indent.contains(' ') && indent.contains('\t') {
+            self.report(line, "indentation mixes tabs and spaces");
+        }
+        let width = indent.chars().fold(0, |width, c| match c {
+            '\t' => (width / TAB_STOP + 1) * TAB_STOP,
+            _ => width + 1,
+        });
+
+        let current = self.indents.last().copied().unwrap_or(0);
+        if width > current {
+            if !self.expect_indent {
+                self.report(line, "unexpected indent");
+            }
+            self.indents.push(width);
+            return;
+        }
+        if self.expect_indent {
+            self.report(
+                line,
+                "expected an indented block after the line ending in `:`",
+            );
+        }
+        while self.indents.last().is_some_and(|&indent| indent > width) {
+            self.indents.pop();
+        }
+        if self.indents.last().copied().unwrap_or(0) != width {
+            self.report(line, "unindent does not match any outer indentation level");
+            self.indents.push(width);
+        }
+    }
+
+    // Follows strings and brackets through one line, returning the last
+    // character of code on it (outside strings and comments)
+    fn scan_line(&mut self, line: usize, text: &str) -> Option<char> {
+        let mut last_code = None;
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            if let Some((delimiter, _)) = self.open_string {
+                match rest.find(delimiter) {
+                    Some(i) => {
+                        rest = &rest[i + delimiter.len()..];
+                        self.open_string = None;
+                        last_code = delimiter.chars().last();
+                        continue;
+                    }
+                    None => return None,
+                }
+            }
+
+            if let Some(delimiter) = ["\"\"\"", "'''"]
+                .into_iter()
+                .find(|delimiter| rest.starts_with(delimiter))
+            {
+                self.open_string = Some((delimiter, line));
+                rest = &rest[delimiter.len()..];
+                continue;
+            }
+
+            let c = rest.chars().next().unwrap_or_default();
+            rest = &rest[c.len_utf8()..];
+            match c {
+                '#' => break,
+                '"' | '\'' => match string_end(rest, c) {
+                    Some(end) => rest = &rest[end..],
+                    None => {
+                        self.report(line, format!("string opened with `{}` is never closed", c));
+                        return None;
+                    }
+                },
+                '(' | '[' | '{' => self.brackets.push((c, line)),
+                ')' | ']' | '}' => match self.brackets.pop() {
+                    Some((open, _)) if closer(open) == c => {}
+                    Some((open, opened)) => self.report(
+                        line,
+                        format!(
+                            "`{}` doesn't match the `{}` opened on line {}",
+                            c, open, opened
+                        ),
+                    ),
+                    None => self.report(line, format!("`{}` has no opening bracket", c)),
+                },
+                _ => {}
+            }
+            if !c.is_whitespace() {
+                last_code = Some(c);
+            }
+        }
+        last_code
+    }
+}
+
+// Problems in the section's code: unbalanced brackets, unclosed strings and
+// indentation Python would reject. Comment lines and fenced regions for other
+// languages aren't checked.
+pub fn check(section: &Section) -> Vec<Problem> {
+    let mut scan = Scan {
+        indents: vec![0],
+        ..Scan::default()
+    };
+
+    for (i, text) in section.content.lines().enumerate() {
+        let line = i + 1;
+        if section.fences.iter().any(|fence| fence.lines.contains(&i)) {
+            continue;
+        }
+
+        let starts_logical_line =
+            scan.open_string.is_none() && scan.brackets.is_empty() && !scan.continued;
+        if starts_logical_line {
+            let code = text.trim_start();
+            if code.is_empty() || code.starts_with('#') {
+                continue;
+            }
+            scan.check_indent(line, &text[..text.len() - code.len()]);
+        }
+
+        let last_code = scan.scan_line(line, text);
+        scan.continued = scan.open_string.is_none() && last_code == Some('\\');
+        if scan.open_string.is_none() && scan.brackets.is_empty() && !scan.continued {
+            scan.expect_indent = last_code == Some(':');
+        }
+    }
+
+    if let Some((_, opened)) = scan.open_string {
+        scan.report(opened, "triple-quoted string is never closed");
+    }
+    for (open, opened) in std::mem::take(&mut scan.brackets) {
+        scan.report(opened, format!("`{}` is never closed", open));
+    }
+    scan.problems.sort_by_key(|problem| problem.line);
+    scan.problems
+}
+
+// Byte offset just past the `quote` closing a single-line string, given the
+// text after its opening quote
+fn string_end(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == quote => return Some(i + c.len_utf8()),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn closer(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
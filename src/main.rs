@@ -1,32 +1,346 @@
-mod cheatsheet;
-mod highlighting;
-mod manager;
+mod cli;
+mod completions;
+mod config;
+mod favorites;
+mod mangen;
+mod watch;
 
-use manager::CheatSheetManager;
-use std::{env, process};
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use cli::{Cli, Command, FavAction, Shell};
+use config::Config;
+use py_cheat::{CheatError, CheatSheetManager, ParseOptions, PythonHighlighter};
+use std::env;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let manager = CheatSheetManager::new();
-
-    let result = match args.len() {
-        1 => {
-            manager.show_available_sheets();
-            Ok(())
-        }
-        2 => manager.show_sheet_outline(&args[1]),
-        3 => {
-            if args[2] == "0" {
-                manager.show_full_sheet(&args[1])
+// `Sheet.N` (or `Sheet.N-M`) is shorthand for `Sheet N`, unless a sheet is
+// named exactly that
+fn split_reference<'a>(
+    manager: &CheatSheetManager,
+    reference: &'a str,
+) -> (Option<&'a str>, Option<&'a str>) {
+    let is_sheet_name = manager
+        .sheet_names()
+        .any(|name| name.eq_ignore_ascii_case(reference));
+    let is_section_number = |section: &str| {
+        section.starts_with(|c: char| c.is_ascii_digit())
+            && section
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == ',')
+    };
+
+    match reference.rsplit_once('.') {
+        Some((sheet, section))
+            if !is_sheet_name && !sheet.is_empty() && is_section_number(section) =>
+        {
+            (Some(sheet), Some(section))
+        }
+        _ => (Some(reference), None),
+    }
+}
+
+// `Sheet`, `Sheet Section` or `Sheet` and `Section` on separate lines, as
+// given to --stdin
+fn read_stdin_reference() -> Result<(Option<String>, Option<String>), CheatError> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|source| CheatError::Io {
+            path: PathBuf::from("standard input"),
+            source,
+        })?;
+
+    let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+    let Some(first) = lines.next() else {
+        return Ok((None, None));
+    };
+    let (sheet, section) = match first.split_once(char::is_whitespace) {
+        Some((sheet, section)) => (sheet, Some(section.trim())),
+        None => (first, lines.next()),
+    };
+    Ok((Some(sheet.to_string()), section.map(str::to_string)))
+}
+
+// $PY_CHEAT_DEFAULT names the sheet a bare `py_cheat` shows instead of the
+// overview of every sheet
+fn default_sheet() -> Option<String> {
+    env::var("PY_CHEAT_DEFAULT")
+        .ok()
+        .map(|sheet| sheet.trim().to_string())
+        .filter(|sheet| !sheet.is_empty())
+}
+
+fn run(manager: &mut CheatSheetManager, cli: &Cli) -> Result<(), CheatError> {
+    // A global flag, so clap can't check this against the top-level ones
+    let quiz = matches!(cli.command, Some(Command::Quiz { .. }));
+    if cli.output.is_some() && (cli.tui || cli.fzf || cli.watch || cli.copy || quiz) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--output can't be used with --tui, --fzf, --watch, --copy or quiz",
+            )
+            .exit();
+    }
+
+    if let Some(dir) = &cli.dir {
+        manager.load_dir(dir)?;
+    }
+    let file_sheet = match &cli.file {
+        Some(path) => Some(manager.load_file(path)?),
+        None => None,
+    };
+
+    match &cli.command {
+        Some(Command::List { page }) => return manager.show_sheet_names(page.offset, page.limit),
+        Some(Command::Random { sheet }) => return manager.show_random(sheet.as_deref()),
+        Some(Command::Toc { page }) => return manager.show_toc(page.offset, page.limit),
+        Some(Command::Quiz { sheet }) => return manager.quiz(sheet),
+        Some(Command::Diff { left, right }) => return manager.show_diff(left, right),
+        Some(Command::Fav { action, index }) => {
+            return match (action, index) {
+                (Some(FavAction::Add { sheet, section }), _) => {
+                    favorites::add(manager, sheet, *section)
+                }
+                (Some(FavAction::Remove { index }), _) => favorites::remove(*index),
+                (None, Some(index)) => favorites::show(manager, *index),
+                (Some(FavAction::List), _) | (None, None) => {
+                    manager.print(&favorites::render_list(manager)?)
+                }
+            };
+        }
+        Some(Command::Completions { shell }) => {
+            let Some(shell) = shell.or_else(Shell::detect) else {
+                let mut command = Cli::command();
+                command.build();
+                command
+                    .find_subcommand_mut("completions")
+                    .expect("completions is a subcommand")
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "could not tell the shell from $SHELL; name one of: bash, zsh, fish",
+                    )
+                    .exit();
+            };
+            return manager.print(&completions::generate(shell, manager)?);
+        }
+        Some(Command::Mangen) => {
+            return manager.print(&mangen::generate());
+        }
+        None => {}
+    }
+
+    if cli.json_schema {
+        return manager.print(&manager.render_json_schema()?);
+    }
+
+    if let Some(path) = &cli.highlight {
+        return manager.show_highlighted(path);
+    }
+
+    if cli.all {
+        return manager.show_all();
+    }
+
+    if let Some(term) = &cli.search {
+        return manager.show_search(term);
+    }
+
+    if let Some(pattern) = &cli.regex {
+        return manager.show_regex_search(pattern);
+    }
+
+    if let Some(query) = &cli.find {
+        return manager.show_find(query);
+    }
+
+    if let Some(number) = cli.compare {
+        return manager.show_compare(number);
+    }
+
+    let (stdin_sheet, stdin_section) = if cli.stdin {
+        let reference = read_stdin_reference()?;
+        if reference.0.is_none() {
+            Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--stdin read no sheet name",
+                )
+                .exit();
+        }
+        reference
+    } else {
+        (None, None)
+    };
+
+    // A loaded file stands in for the sheet argument when none is given
+    let (sheet, section) = match (
+        cli.sheet
+            .as_deref()
+            .or(stdin_sheet.as_deref())
+            .or(file_sheet.as_deref()),
+        cli.section.as_deref().or(stdin_section.as_deref()),
+    ) {
+        (Some(sheet), None) => split_reference(manager, sheet),
+        given => given,
+    };
+
+    if cli.stats {
+        return manager.show_stats(sheet);
+    }
+
+    if cli.lint {
+        return manager.show_lint(sheet);
+    }
+
+    if cli.tui {
+        return manager.browse(sheet);
+    }
+
+    if cli.fzf {
+        return manager.pick_with_fzf(sheet);
+    }
+
+    if cli.copy {
+        return match sheet {
+            Some(sheet) => manager.copy_to_clipboard(sheet, section),
+            None => Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--copy requires a sheet name",
+                )
+                .exit(),
+        };
+    }
+
+    if cli.raw {
+        return match sheet {
+            Some(sheet) => manager.show_raw(sheet),
+            None => Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--raw requires a sheet name",
+                )
+                .exit(),
+        };
+    }
+
+    match (sheet, section) {
+        (_, None) if cli.section_title => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--section-title requires a sheet and a section",
+            )
+            .exit(),
+        (_, None) if cli.extract.is_some() => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--extract requires a sheet and a section",
+            )
+            .exit(),
+        (None, _) if !cli.only.is_empty() => manager.show_available_sheets(&cli.only),
+        (None, _) => match default_sheet() {
+            Some(sheet) => manager.show_sheet_outline(&sheet),
+            None => manager.show_available_sheets(&[]),
+        },
+        (Some(sheet), None) if cli.tree => manager.show_sheet_tree(sheet),
+        (Some(sheet), None) if cli.summary => manager.show_summary(sheet),
+        (Some(sheet), None) => manager.show_sheet_outline(sheet),
+        (Some(sheet), Some(section)) if cli.section_title => {
+            manager.show_section_title(sheet, section)
+        }
+        (Some(sheet), Some(section)) if cli.extract.is_some() => {
+            manager.show_extract(sheet, section, cli.extract.as_deref().unwrap_or_default())
+        }
+        (Some(sheet), Some(section)) => {
+            if section == "0" {
+                manager.show_full_sheet(sheet)
             } else {
-                manager.show_section(&args[1], &args[2])
+                manager.show_section(sheet, section)
             }
         }
-        _ => Err("Usage: py_cheat [sheet_name] [section_number]".into()),
-    };
+    }
+}
+
+fn build_manager(cli: &Cli, config: &Config) -> CheatSheetManager {
+    let mut manager = CheatSheetManager::with_color(cli.color_enabled(config));
+    manager.set_format(cli.format.or(config.format()).unwrap_or_default());
+    manager.set_pager(!cli.no_pager && config.pager != Some(false));
+    manager.set_line_numbers(cli.line_numbers || config.line_numbers == Some(true));
+    manager.set_code_only(cli.code_only);
+    manager.set_body_only(cli.body_only);
+    manager.set_minify(cli.minify, cli.keep_inline_comments);
+    manager.set_explain(cli.explain);
+    manager.set_box(cli.boxed);
+    manager.set_separator(cli.separator.clone());
+    manager.set_reverse(cli.reverse);
+    manager.set_max_bytes(cli.max_bytes);
+    manager.set_tab_width(cli.tab_width.map(usize::from));
+    manager.set_show_whitespace(cli.show_whitespace);
+    manager.set_hyperlinks(cli.hyperlinks);
+    manager.set_highlight_keywords(cli.highlight_keyword.clone());
+    manager.set_search_context(cli.context);
+    manager.set_count_only(cli.count);
+    manager.set_authored_numbers(cli.authored_numbers);
+    manager.set_width(cli.width(config));
+    manager.set_output(cli.output.clone());
+    manager.set_wrap(cli.wrap(config));
+    manager.set_columns(cli.columns);
+    manager.set_plain_headers(cli.plain || cli.plain_headers || config.plain_headers == Some(true));
+    manager.set_title_width(cli.title_width(config));
+    manager.set_plain_framing(cli.plain);
+    manager.set_theme(cli.theme.or(config.theme()).unwrap_or_default());
+    manager.set_aliases(config.aliases.clone());
+
+    let defaults = ParseOptions::default();
+    manager.set_parse_options(ParseOptions {
+        marker: cli
+            .marker
+            .clone()
+            .or_else(|| config.parse.marker.clone())
+            .unwrap_or(defaults.marker),
+        title_separator: cli
+            .title_separator
+            .clone()
+            .or_else(|| config.parse.title_separator.clone())
+            .unwrap_or(defaults.title_separator),
+    });
+    manager.set_color_depth(cli.color_depth(config));
+    manager
+}
 
-    if let Err(e) = result {
+fn main() {
+    let cli = Cli::parse_with_sheet_list();
+    let config = Config::load().unwrap_or_else(|e| {
+        let highlighter = PythonHighlighter::with_color(cli.color_enabled(&Config::default()));
+        eprintln!("{}", highlighter.format_error(&e.to_string()));
+        process::exit(e.exit_code());
+    });
+
+    let mut manager = build_manager(&cli, &config);
+
+    if let Some(path) = cli.file.as_deref().filter(|_| cli.watch) {
+        let result = watch::watch(path, || {
+            // A fresh manager per redraw re-reads the file
+            let mut manager = build_manager(&cli, &config);
+            manager.set_pager(false);
+            if let Err(e) = run(&mut manager, &cli) {
+                eprintln!("{}", manager.format_error(&e.to_string()));
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("{}", manager.format_error(&e.to_string()));
+            process::exit(e.exit_code());
+        }
+        return;
+    }
+
+    if let Err(e) = run(&mut manager, &cli) {
+        if e.is_broken_pipe() {
+            return;
+        }
         eprintln!("{}", manager.format_error(&e.to_string()));
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
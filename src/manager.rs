@@ -1,105 +1,2102 @@
-use crate::cheatsheet::CheatSheet;
-use crate::highlighting::PythonHighlighter;
-use std::error::Error;
+use crate::cheatsheet::{CheatSheet, ParseOptions, Section};
+use crate::clipboard;
+use crate::diff::SheetDiff;
+use crate::error::CheatError;
+use crate::format::OutputFormat;
+use crate::fuzzy::{self, closest_match};
+use crate::fzf;
+use crate::highlighting::python::find_matches;
+use crate::highlighting::{ColorDepth, PythonHighlighter, Theme};
+use crate::html;
+use crate::index::SearchIndex;
+use crate::layout;
+use crate::lint;
+use crate::markdown;
+use crate::pager;
+use crate::quiz::{self, Question};
+use crate::references;
+use crate::selection::{self, Numbering};
+use crate::stats::{self, SheetStats};
+use crate::tui;
+use regex::Regex;
+use schemars::generate::SchemaSettings;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 const BASICS_PY: &str = include_str!("Basics.py");
 const INTERMEDIATE_PY: &str = include_str!("Intermediate.py");
 const ADVANCED_PY: &str = include_str!("Advanced.py");
 
+// (name, content) of the sheets compiled into the binary
+pub const BUILTIN_SHEETS: [(&str, &str); 3] = [
+    ("Basics", BASICS_PY),
+    ("Intermediate", INTERMEDIATE_PY),
+    ("Advanced", ADVANCED_PY),
+];
+
+#[derive(Serialize)]
+struct SheetEntry<'a> {
+    name: &'a str,
+    sections: &'a [Section],
+}
+
+#[derive(Serialize)]
+struct HitCount<'a> {
+    sheet: &'a str,
+    sections: usize,
+}
+
+#[derive(Serialize)]
+struct HitCounts<'a> {
+    sheets: Vec<HitCount<'a>>,
+    total: usize,
+}
+
+#[derive(Serialize)]
+pub struct LintEntry<'a> {
+    pub sheet: &'a str,
+    pub section: usize,
+    pub title: &'a str,
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct SummaryEntry<'a> {
+    section: usize,
+    title: &'a str,
+    summary: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct TocEntry<'a> {
+    sheet: &'a str,
+    section: usize,
+    title: &'a str,
+}
+
+#[derive(Serialize)]
+struct SearchHit<'a> {
+    sheet: &'a str,
+    section: usize,
+    title: &'a str,
+    content: &'a str,
+}
+
+// A sheet's source text plus its parsed form, computed on first use
+struct LoadedSheet {
+    name: Cow<'static, str>,
+    content: Cow<'static, str>,
+    options: ParseOptions,
+    parsed: OnceCell<CheatSheet>,
+}
+
+impl LoadedSheet {
+    fn new(name: Cow<'static, str>, content: Cow<'static, str>, options: ParseOptions) -> Self {
+        Self {
+            name,
+            content,
+            options,
+            parsed: OnceCell::new(),
+        }
+    }
+
+    fn cheat_sheet(&self) -> Result<&CheatSheet, CheatError> {
+        if let Some(cheat_sheet) = self.parsed.get() {
+            return Ok(cheat_sheet);
+        }
+        let cheat_sheet = CheatSheet::parse_with(&self.content, &self.options)?;
+        Ok(self.parsed.get_or_init(|| cheat_sheet))
+    }
+
+    // The section at zero-based `index`, taken from the parsed sheet once
+    // there is one, else parsed on its own
+    fn section(&self, index: usize) -> Result<Option<Cow<'_, Section>>, CheatError> {
+        match self.parsed.get() {
+            Some(cheat_sheet) => Ok(cheat_sheet.sections.get(index).map(Cow::Borrowed)),
+            None => Ok(
+                CheatSheet::parse_nth_with(&self.content, index, &self.options)?.map(Cow::Owned),
+            ),
+        }
+    }
+}
+
 pub struct CheatSheetManager {
-    sheets: Vec<(&'static str, &'static str)>,
+    sheets: Vec<LoadedSheet>,
     highlighter: PythonHighlighter,
+    format: OutputFormat,
+    pager: bool,
+    line_numbers: bool,
+    code_only: bool,
+    body_only: bool,
+    explain: bool,
+    // Some(keep inline comments) with --minify
+    minify: Option<bool>,
+    reverse: bool,
+    plain_framing: bool,
+    max_bytes: Option<usize>,
+    tab_width: Option<usize>,
+    search_context: Option<usize>,
+    count_only: bool,
+    numbering: Numbering,
+    width: Option<usize>,
+    // Cross-references already warned about, so each is reported once
+    unresolved_references: RefCell<HashSet<String>>,
+    output: Option<PathBuf>,
+    wrap: bool,
+    boxed: bool,
+    separator: Option<String>,
+    columns: bool,
+    plain_headers: bool,
+    title_width: Option<usize>,
+    // (alias, sheet name) pairs, tried before the sheet names themselves
+    aliases: Vec<(String, String)>,
+    // How sheets added from now on find their sections; built-in sheets
+    // always use the defaults
+    parse_options: ParseOptions,
+    // Built on the first search, dropped whenever the sheets change
+    index: OnceCell<SearchIndex>,
 }
 
 impl CheatSheetManager {
     pub fn new() -> Self {
-        let sheets = vec![
-            ("Basics", BASICS_PY),
-            ("Intermediate", INTERMEDIATE_PY),
-            ("Advanced", ADVANCED_PY),
-        ];
+        Self::with_color(true)
+    }
+
+    pub fn with_color(color: bool) -> Self {
+        let sheets = BUILTIN_SHEETS
+            .into_iter()
+            .map(|(name, content)| {
+                LoadedSheet::new(
+                    Cow::Borrowed(name),
+                    Cow::Borrowed(content),
+                    ParseOptions::default(),
+                )
+            })
+            .collect();
 
         Self {
             sheets,
-            highlighter: PythonHighlighter::new(),
+            highlighter: PythonHighlighter::with_color(color),
+            format: OutputFormat::default(),
+            pager: false,
+            line_numbers: false,
+            code_only: false,
+            body_only: false,
+            explain: false,
+            minify: None,
+            reverse: false,
+            plain_framing: false,
+            max_bytes: None,
+            tab_width: None,
+            search_context: None,
+            count_only: false,
+            numbering: Numbering::default(),
+            width: None,
+            unresolved_references: RefCell::new(HashSet::new()),
+            output: None,
+            wrap: false,
+            boxed: false,
+            separator: None,
+            columns: false,
+            plain_headers: false,
+            title_width: None,
+            aliases: Vec::new(),
+            parse_options: ParseOptions::default(),
+            index: OnceCell::new(),
         }
     }
 
-    fn get_sheet_content(&self, name: &str) -> Option<&'static str> {
+    pub fn set_format(&mut self, format: OutputFormat) {
+        self.format = format;
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    // When enabled, `show_*` output taller than the terminal goes through $PAGER
+    pub fn set_pager(&mut self, pager: bool) {
+        self.pager = pager;
+    }
+
+    // Prefix displayed section and sheet lines with line numbers
+    pub fn set_line_numbers(&mut self, line_numbers: bool) {
+        self.line_numbers = line_numbers;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.highlighter.set_theme(theme);
+    }
+
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.highlighter.set_color_depth(depth);
+    }
+
+    // Show sections as runnable code only, without framing or prose comments
+    pub fn set_code_only(&mut self, code_only: bool) {
+        self.code_only = code_only;
+    }
+
+    // Show sections without their `# ----` and `# N. Title` framing, keeping
+    // their comments
+    pub fn set_body_only(&mut self, body_only: bool) {
+        self.body_only = body_only;
+    }
+
+    // Show each run of comments with the code after it hung underneath
+    pub fn set_explain(&mut self, explain: bool) {
+        self.explain = explain;
+    }
+
+    // Cut each displayed section's content to at most this many bytes
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+    }
+
+    // Expand tabs in displayed sections to spaces, `tab_width` columns apart
+    pub fn set_tab_width(&mut self, tab_width: Option<usize>) {
+        self.tab_width = tab_width;
+    }
+
+    pub fn set_show_whitespace(&mut self, show_whitespace: bool) {
+        self.highlighter.set_show_whitespace(show_whitespace);
+    }
+
+    pub fn set_hyperlinks(&mut self, hyperlinks: bool) {
+        self.highlighter.set_hyperlinks(hyperlinks);
+    }
+
+    pub fn set_highlight_keywords(&mut self, words: Vec<String>) {
+        self.highlighter.set_emphasized_words(words);
+    }
+
+    // Select sections, and number them in section trees, by the `N.` written
+    // in the file instead of by position. Search results, --find and --stats
+    // keep numbering by position; JSON output carries the written number
+    // alongside the section's place in the list.
+    pub fn set_authored_numbers(&mut self, authored: bool) {
+        self.numbering = if authored {
+            Numbering::Authored
+        } else {
+            Numbering::Position
+        };
+    }
+
+    fn resolve(&self, section_spec: &str, sections: &[Section]) -> Result<Vec<usize>, CheatError> {
+        selection::resolve(section_spec, sections, self.numbering)
+    }
+
+    // Show search hits as their matching lines with this many lines around
+    // each, rather than as whole sections
+    pub fn set_search_context(&mut self, search_context: Option<usize>) {
+        self.search_context = search_context;
+    }
+
+    // Report search hits as a count of matching sections per sheet, like
+    // `grep -c`, instead of showing them
+    pub fn set_count_only(&mut self, count_only: bool) {
+        self.count_only = count_only;
+    }
+
+    // The output width every layout shares, or None when it's unknown (not
+    // a terminal); wrapping then assumes 80 columns and nothing is laid out
+    // side by side
+    pub fn set_width(&mut self, width: Option<usize>) {
+        self.width = width;
+    }
+
+    // Write output to this file, created or overwritten, instead of stdout
+    pub fn set_output(&mut self, output: Option<PathBuf>) {
+        self.output = output;
+    }
+
+    // Draw a box, titled with the section, around each displayed section
+    pub fn set_box(&mut self, boxed: bool) {
+        self.boxed = boxed;
+    }
+
+    // Print this line between sections shown together instead of a blank
+    // line; `rule` draws a rule across the width
+    pub fn set_separator(&mut self, separator: Option<String>) {
+        self.separator = separator;
+    }
+
+    // Ends one of several sections printed together with the separator,
+    // first ending its last line if it's unfinished
+    fn push_separator(&self, output: &mut String) {
+        let Some(separator) = &self.separator else {
+            output.push('\n');
+            return;
+        };
+        let last_line = output.rsplit('\n').next().unwrap_or_default();
+        if !layout::strip_ansi(last_line).is_empty() {
+            output.push('\n');
+        }
+        match separator.as_str() {
+            "rule" => {
+                let rule = "─".repeat(self.width.unwrap_or(layout::DEFAULT_WIDTH));
+                output.push_str(&self.highlighter.format_line_number(&rule));
+            }
+            _ => output.push_str(separator),
+        }
+        output.push('\n');
+    }
+
+    // Soft-wrap displayed section and sheet lines at the width
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    // Lay the sheet overview, and --compare's sections, out in as many
+    // columns as fit the width
+    pub fn set_columns(&mut self, columns: bool) {
+        self.columns = columns;
+    }
+
+    fn wrap_width(&self) -> Option<usize> {
+        self.wrap
+            .then(|| self.width.unwrap_or(layout::DEFAULT_WIDTH))
+    }
+
+    fn columns_width(&self) -> Option<usize> {
+        self.width.filter(|_| self.columns)
+    }
+
+    // Show sections as just their code lines, without blank lines, prose
+    // comments and (unless `inline_comments`) inline comments
+    pub fn set_minify(&mut self, minify: bool, inline_comments: bool) {
+        self.minify = minify.then_some(inline_comments);
+    }
+
+    // List and print sections last first; they keep their numbers
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.reverse = reverse;
+    }
+
+    // Introduce displayed sections with a bare `N. Title` line rather than
+    // their comment framing
+    pub fn set_plain_framing(&mut self, plain_framing: bool) {
+        self.plain_framing = plain_framing;
+    }
+
+    // Draw section trees with `-` bullets, for fonts and screen readers that
+    // don't cope with box-drawing characters
+    pub fn set_plain_headers(&mut self, plain_headers: bool) {
+        self.plain_headers = plain_headers;
+    }
+
+    // Cut section titles in sheet overviews so each tree line fits this many
+    // columns, ending them in `…`
+    pub fn set_title_width(&mut self, title_width: Option<usize>) {
+        self.title_width = title_width;
+    }
+
+    // Section markers for sheets added afterwards, e.g. with --file or --dir
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    // Short names that stand for sheets, such as `int` for Intermediate
+    pub fn set_aliases(&mut self, aliases: impl IntoIterator<Item = (String, String)>) {
+        self.aliases = aliases.into_iter().collect();
+    }
+
+    pub(crate) fn highlight_code(&self, code: &str) -> String {
+        self.highlight_code_within(code, self.wrap_width())
+    }
+
+    // `highlight_code`, wrapping (when wrapping at all) at `wrap_width`
+    fn highlight_code_within(&self, code: &str, wrap_width: Option<usize>) -> String {
+        let highlighted = self.highlighter.highlight(&self.titled_references(code));
+        if !self.line_numbers && !self.wrap {
+            return highlighted;
+        }
+
+        let format_number = |number: &str| self.highlighter.format_line_number(number);
+        let format_number: Option<&dyn Fn(&str) -> String> = if self.line_numbers {
+            Some(&format_number)
+        } else {
+            None
+        };
+        layout::layout_lines(&highlighted, wrap_width, format_number)
+    }
+
+    // The sheet name, number and title of the one section a `[[Sheet#N]]`
+    // cross-reference picks, warning (once) when it picks none
+    fn resolve_reference(
+        &self,
+        sheet_name: &str,
+        section_spec: &str,
+    ) -> Option<(&str, usize, &str)> {
+        let resolved = self.find_sheet(sheet_name).ok().and_then(|sheet| {
+            let sections = &sheet.cheat_sheet().ok()?.sections;
+            match self.resolve(section_spec, sections).ok()?[..] {
+                [i] => Some((sheet.name.as_ref(), i + 1, sections[i].title.as_str())),
+                _ => None,
+            }
+        });
+        let reference = format!("[[{}#{}]]", sheet_name, section_spec);
+        if resolved.is_none()
+            && self
+                .unresolved_references
+                .borrow_mut()
+                .insert(reference.clone())
+        {
+            self.warn(&format!(
+                "Cross-reference {} doesn't name one section",
+                reference
+            ));
+        }
+        resolved
+    }
+
+    // Cross-references written out as `Sheet → N. Title`, for terminal and
+    // Markdown output (whose sections are code blocks, which can't link)
+    fn titled_references<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        references::replace(text, |sheet, section| {
+            let (sheet, number, title) = self.resolve_reference(sheet, section)?;
+            Some(format!("{} → {}. {}", sheet, number, title))
+        })
+    }
+
+    fn markdown_output(&self, markdown: String) -> String {
+        match self.titled_references(&markdown) {
+            Cow::Owned(titled) => titled,
+            Cow::Borrowed(_) => markdown,
+        }
+    }
+
+    // A standalone HTML page whose cross-references link to the sections
+    // they name
+    fn html_document(&self, title: &str, body: &str) -> String {
+        let body = references::replace(body, |sheet, section| {
+            let (sheet, number, title) = self.resolve_reference(sheet, section)?;
+            Some(format!(
+                "<a href=\"#{}\">{} → {}. {}</a>",
+                html::anchor(sheet, number),
+                sheet,
+                number,
+                title
+            ))
+        });
+        html::document(title, &body)
+    }
+
+    fn emit(&self, output: &str) -> Result<(), CheatError> {
+        if self.pager && self.output.is_none() {
+            pager::print_paged(output).map_err(stdout_error)
+        } else {
+            self.print(output)
+        }
+    }
+
+    // Writes `output` as is, never paged, to stdout or the output file
+    pub fn print(&self, output: &str) -> Result<(), CheatError> {
+        let Some(path) = &self.output else {
+            return pager::print(output).map_err(stdout_error);
+        };
+        fs::write(path, output).map_err(|source| self.output_error(source))?;
+        eprintln!("Wrote {}", path.display());
+        Ok(())
+    }
+
+    fn output_error(&self, source: io::Error) -> CheatError {
+        match &self.output {
+            Some(path) => CheatError::Write {
+                path: path.clone(),
+                source,
+            },
+            None => stdout_error(source),
+        }
+    }
+
+    // Registers an extra sheet, replacing any existing sheet with the same name.
+    // Returns true if a sheet was replaced.
+    pub fn add_sheet(&mut self, name: impl Into<String>, content: impl Into<String>) -> bool {
+        let name = name.into();
+        let sheet = LoadedSheet::new(
+            Cow::Owned(name),
+            Cow::Owned(content.into()),
+            self.parse_options.clone(),
+        );
+        self.index = OnceCell::new();
+        match self
+            .sheets
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&sheet.name))
+        {
+            Some(existing) => {
+                *existing = sheet;
+                true
+            }
+            None => {
+                self.sheets.push(sheet);
+                false
+            }
+        }
+    }
+
+    // Loads a cheat sheet file as a sheet named after the file stem
+    pub fn load_file(&mut self, path: &Path) -> Result<String, CheatError> {
+        let io_error = |source| CheatError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                io_error(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid file name",
+                ))
+            })?
+            .to_string();
+        let content = fs::read_to_string(path).map_err(io_error)?;
+        let options = &self.parse_options;
+        if let Err(e) = CheatSheet::validate_numbering_with(&content, options) {
+            self.warn(&format!("{}: {}", path.display(), e));
+        }
+        // Custom markers that find nothing are most likely mistyped
+        let found_nothing = CheatSheet::parse_with(&content, options)
+            .is_ok_and(|cheat_sheet| cheat_sheet.sections.is_empty());
+        if !options.is_default() && found_nothing {
+            self.warn(&format!(
+                "{}: no line starts with the marker \"{}\", a number and the separator \"{}\"",
+                path.display(),
+                options.marker,
+                options.title_separator
+            ));
+        }
+
+        if self.add_sheet(name.clone(), content) {
+            self.warn(&format!(
+                "{} replaces the existing sheet {}",
+                path.display(),
+                name
+            ));
+        }
+        Ok(name)
+    }
+
+    // Loads every `.py` file in a directory, in file name order
+    pub fn load_dir(&mut self, path: &Path) -> Result<Vec<String>, CheatError> {
+        let io_error = |source| CheatError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let entries = fs::read_dir(path).map_err(io_error)?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let file_path = entry.map_err(io_error)?.path();
+            if file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "py") {
+                files.push(file_path);
+            }
+        }
+        files.sort();
+
+        files.iter().map(|file| self.load_file(file)).collect()
+    }
+
+    fn warn(&self, message: &str) {
+        eprintln!("{}", self.highlighter.format_warning(message));
+    }
+
+    pub fn sheet_names(&self) -> impl Iterator<Item = &str> {
+        self.sheets.iter().map(|sheet| sheet.name.as_ref())
+    }
+
+    fn get_sheet(&self, name: &str) -> Option<&LoadedSheet> {
+        let name = self
+            .aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+            .map_or(name, |(_, sheet_name)| sheet_name);
         self.sheets
             .iter()
-            .find(|(sheet_name, _)| *sheet_name == name)
-            .map(|(_, content)| *content)
+            .find(|sheet| sheet.name.eq_ignore_ascii_case(name))
+    }
+
+    // The sheets named in `only`, in their usual order, or all of them when
+    // it's empty; names that aren't sheets (or aliases) are all reported
+    fn selected_sheets(&self, only: &[String]) -> Result<Vec<&LoadedSheet>, CheatError> {
+        if only.is_empty() {
+            return Ok(self.sheets.iter().collect());
+        }
+        let unknown: Vec<String> = only
+            .iter()
+            .filter(|name| self.get_sheet(name).is_none())
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            return Err(CheatError::UnknownSheets {
+                names: unknown,
+                known: self.sheet_names().map(str::to_string).collect(),
+            });
+        }
+        let selected: Vec<&str> = only
+            .iter()
+            .filter_map(|name| self.get_sheet(name))
+            .map(|sheet| sheet.name.as_ref())
+            .collect();
+        Ok(self
+            .sheets
+            .iter()
+            .filter(|sheet| selected.contains(&sheet.name.as_ref()))
+            .collect())
+    }
+
+    // The aliases standing for `sheet_name`, in the order they were defined
+    fn aliases_of<'a>(&'a self, sheet_name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.aliases
+            .iter()
+            .filter(move |(_, name)| name.eq_ignore_ascii_case(sheet_name))
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    fn find_sheet(&self, name: &str) -> Result<&LoadedSheet, CheatError> {
+        self.get_sheet(name)
+            .ok_or_else(|| CheatError::SheetNotFound {
+                name: name.to_string(),
+                suggestion: closest_match(name, self.sheet_names()).map(str::to_string),
+            })
+    }
+
+    // Parsed sheets are cached, so repeated lookups don't reparse
+    pub fn parse_sheet(&self, sheet_name: &str) -> Result<&CheatSheet, CheatError> {
+        self.find_sheet(sheet_name)?.cheat_sheet()
+    }
+
+    // Pretty-printed JSON, or for --format jsonl the value on one line, each
+    // item of a list on its own
+    pub fn render_json<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, CheatError> {
+        if self.format != OutputFormat::JsonLines {
+            return Ok(format!("{}\n", serde_json::to_string_pretty(value)?));
+        }
+        let items = match serde_json::to_value(value)? {
+            serde_json::Value::Array(items) => items,
+            value => vec![value],
+        };
+        items
+            .iter()
+            .map(|item| Ok(format!("{}\n", serde_json::to_string(item)?)))
+            .collect()
+    }
+
+    // JSON Schema for a whole sheet as `--format json` prints it, with the
+    // schema of a single section among its definitions
+    pub fn render_json_schema(&self) -> Result<String, CheatError> {
+        let generator = SchemaSettings::default().for_serialize().into_generator();
+        self.render_json(&generator.into_root_schema_for::<CheatSheet>())
+    }
+
+    // Sheet names one per line, without parsing or highlighting; `offset` and
+    // `limit` slice the list as for `render_toc`
+    pub fn render_sheet_names(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<String, CheatError> {
+        let all_names: Vec<&str> = self.sheet_names().collect();
+        let names = page(&all_names, offset, limit);
+        if self.format.is_json() {
+            return self.render_json(&names);
+        }
+        Ok(names.iter().map(|name| format!("{}\n", name)).collect())
+    }
+
+    // The overview of every sheet, or with `only` of just the sheets named
+    pub fn render_available_sheets(&self, only: &[String]) -> Result<String, CheatError> {
+        let parsed: Vec<(&str, &CheatSheet)> = self
+            .selected_sheets(only)?
+            .into_iter()
+            .filter_map(|sheet| match sheet.cheat_sheet() {
+                Ok(cheat_sheet) => Some((sheet.name.as_ref(), cheat_sheet)),
+                // One broken sheet shouldn't hide the others
+                Err(e) => {
+                    self.warn(&format!("Skipping sheet {}: {}", sheet.name, e));
+                    None
+                }
+            })
+            .collect();
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                let entries: Vec<SheetEntry> = parsed
+                    .iter()
+                    .map(|(name, cheat_sheet)| SheetEntry {
+                        name,
+                        sections: &cheat_sheet.sections,
+                    })
+                    .collect();
+                return self.render_json(&entries);
+            }
+            OutputFormat::Markdown => {
+                let outlines: Vec<String> = parsed
+                    .iter()
+                    .map(|(name, cheat_sheet)| markdown::outline(name, &cheat_sheet.sections))
+                    .collect();
+                return Ok(outlines.join("\n"));
+            }
+            OutputFormat::Html => {
+                let outlines: String = parsed
+                    .iter()
+                    .map(|(name, cheat_sheet)| html::outline(name, &cheat_sheet.sections))
+                    .collect();
+                return Ok(self.html_document("Python cheat sheets", &outlines));
+            }
+            OutputFormat::Text => {}
+        }
+
+        let blocks: Vec<String> = parsed
+            .iter()
+            .map(|(sheet_name, cheat_sheet)| {
+                let count = cheat_sheet.sections.len();
+                let plural = if count == 1 { "" } else { "s" };
+                let aliases: Vec<&str> = self.aliases_of(sheet_name).collect();
+                let aliases = match aliases.is_empty() {
+                    true => String::new(),
+                    false => format!("; alias {}", aliases.join(", ")),
+                };
+                let mut block = String::new();
+                self.render_title(
+                    &mut block,
+                    &format!("{} ({} section{}{})", sheet_name, count, plural, aliases),
+                );
+                self.render_sections(&mut block, &cheat_sheet.sections);
+                block
+            })
+            .collect();
+
+        let columns = self
+            .columns_width()
+            .map_or(1, |width| layout::column_count(&blocks, width));
+        let mut output = String::new();
+        for row in blocks.chunks(columns) {
+            output.push('\n');
+            output.push_str(&layout::side_by_side(row));
+        }
+        Ok(output)
     }
 
-    pub fn show_available_sheets(&self) {
-        for (sheet_name, content) in &self.sheets {
-            if let Ok(cheat_sheet) = CheatSheet::parse(content) {
-                println!("\n{}", self.highlighter.format_header(sheet_name, true));
-                self.print_sections(&cheat_sheet.sections);
+    pub fn render_sheet_outline(&self, sheet_name: &str) -> Result<String, CheatError> {
+        let sheet = self.find_sheet(sheet_name)?;
+
+        let cheat_sheet = sheet.cheat_sheet()?;
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => return self.render_json(cheat_sheet),
+            OutputFormat::Markdown => {
+                return Ok(markdown::outline(&sheet.name, &cheat_sheet.sections))
+            }
+            OutputFormat::Html => {
+                let outline = html::outline(&sheet.name, &cheat_sheet.sections);
+                return Ok(self.html_document(&sheet.name, &outline));
             }
+            OutputFormat::Text => {}
         }
+
+        let mut output = String::new();
+        self.render_title(&mut output, &sheet.name);
+        self.render_sections(&mut output, &cheat_sheet.sections);
+        Ok(output)
     }
 
-    pub fn show_sheet_outline(&self, sheet_name: &str) -> Result<(), Box<dyn Error>> {
-        let content = self
-            .get_sheet_content(sheet_name)
-            .ok_or_else(|| format!("Could not find sheet {}", sheet_name))?;
+    // One `N. Title: first line` per section, the first line being the
+    // section's first prose comment or else its first code line
+    pub fn render_summary(&self, sheet_name: &str) -> Result<String, CheatError> {
+        let sections = &self.parse_sheet(sheet_name)?.sections;
+        let entries: Vec<SummaryEntry> = sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| SummaryEntry {
+                section: match self.numbering {
+                    Numbering::Position => i + 1,
+                    Numbering::Authored => section.number as usize,
+                },
+                title: &section.title,
+                summary: section.first_meaningful_line(),
+            })
+            .collect();
 
-        let cheat_sheet = CheatSheet::parse(content)?;
-        println!("{}", self.highlighter.format_header(sheet_name, true));
-        self.print_sections(&cheat_sheet.sections);
-        Ok(())
+        if self.format.is_json() {
+            return self.render_json(&entries);
+        }
+        Ok(entries
+            .iter()
+            .map(|entry| match entry.summary {
+                Some(summary) => format!("{}. {}: {}\n", entry.section, entry.title, summary),
+                None => format!("{}. {}\n", entry.section, entry.title),
+            })
+            .collect())
+    }
+
+    // A section as displayed: as parsed, cut down to code with --code-only or
+    // --minify or to its body with --body-only, then truncated with
+    // --max-bytes
+    fn displayed<'a>(&self, section: &'a Section) -> Cow<'a, Section> {
+        let section = if let Some(inline_comments) = self.minify {
+            Cow::Owned(section.minified(inline_comments))
+        } else if self.code_only {
+            Cow::Owned(section.code_only())
+        } else if self.explain {
+            Cow::Owned(section.explained())
+        } else if self.body_only {
+            Cow::Owned(section.body_only())
+        } else if self.plain_framing {
+            Cow::Owned(section.plainly_framed())
+        } else {
+            Cow::Borrowed(section)
+        };
+        let section = match self.tab_width {
+            Some(tab_width) => Cow::Owned(section.tabs_expanded(tab_width)),
+            None => section,
+        };
+        match self.max_bytes {
+            Some(max_bytes) => Cow::Owned(section.truncated(max_bytes)),
+            None => section,
+        }
+    }
+
+    // Whether `displayed` can change a section, so whole sheets must be
+    // shown section by section rather than as the stored file
+    fn transforms_sections(&self) -> bool {
+        self.code_only
+            || self.body_only
+            || self.explain
+            || self.minify.is_some()
+            || self.plain_framing
+            || self.max_bytes.is_some()
+            || self.tab_width.is_some()
+    }
+
+    fn displayed_sections<'a>(
+        &self,
+        sections: impl IntoIterator<Item = &'a Section>,
+    ) -> Vec<Cow<'a, Section>> {
+        sections
+            .into_iter()
+            .map(|section| self.displayed(section))
+            .collect()
     }
 
-    fn print_sections(&self, sections: &[crate::cheatsheet::Section]) {
-        for (i, section) in sections.iter().enumerate() {
-            let prefix = if i == sections.len() - 1 {
+    // Just the sheet's `├──`/`└──` section tree, without the title line
+    pub fn render_sheet_tree(&self, sheet_name: &str) -> Result<String, CheatError> {
+        if self.format != OutputFormat::Text {
+            return self.render_sheet_outline(sheet_name);
+        }
+        let mut output = String::new();
+        self.render_sections(&mut output, &self.parse_sheet(sheet_name)?.sections);
+        Ok(output)
+    }
+
+    fn render_title(&self, output: &mut String, title: &str) {
+        output.push_str(&self.highlighter.format_header(title, true));
+        output.push('\n');
+    }
+
+    fn render_sections(&self, output: &mut String, sections: &[Section]) {
+        if sections.is_empty() {
+            output.push_str(&self.highlighter.format_header("(no sections)", false));
+            output.push('\n');
+            return;
+        }
+        let mut order: Vec<usize> = (0..sections.len()).collect();
+        if self.reverse {
+            order.reverse();
+        }
+        for (position, &i) in order.iter().enumerate() {
+            let section = &sections[i];
+            let prefix = if self.plain_headers {
+                "-"
+            } else if position == sections.len() - 1 {
                 "└──"
             } else {
                 "├──"
             };
-            let header = format!("{} {}. {}", prefix, i + 1, section.title);
-            println!("{}", self.highlighter.format_header(&header, false));
+            let number = match self.numbering {
+                Numbering::Position => i + 1,
+                Numbering::Authored => section.number as usize,
+            };
+            let mut header = format!("{} {}. {}", prefix, number, section.title);
+            if let Some(width) = self.title_width {
+                header = layout::ellipsize(&header, width);
+            }
+            output.push_str(&self.highlighter.format_header(&header, false));
+            output.push('\n');
+        }
+    }
+
+    pub fn render_section(
+        &self,
+        sheet_name: &str,
+        section_number: &str,
+    ) -> Result<String, CheatError> {
+        let (indices, selected) = match self.quick_section(sheet_name, section_number)? {
+            Some((index, section)) => (vec![index], vec![section]),
+            None => {
+                let cheat_sheet = self.parse_sheet(sheet_name)?;
+                let mut indices = self.resolve(section_number, &cheat_sheet.sections)?;
+                if self.reverse {
+                    indices.reverse();
+                }
+                let selected = indices
+                    .iter()
+                    .map(|&i| Cow::Borrowed(&cheat_sheet.sections[i]))
+                    .collect();
+                (indices, selected)
+            }
+        };
+        self.render_selected(sheet_name, &indices, &selected)
+    }
+
+    // The section at `position`, counting from 1 whatever the numbering, for
+    // callers that located it by position rather than by what was typed
+    pub fn render_section_at(
+        &self,
+        sheet_name: &str,
+        position: usize,
+    ) -> Result<String, CheatError> {
+        let section = match position.checked_sub(1) {
+            Some(index) => self.find_sheet(sheet_name)?.section(index)?,
+            None => None,
+        };
+        match section {
+            Some(section) => self.render_selected(sheet_name, &[position - 1], &[section]),
+            None => Err(CheatError::SectionOutOfRange {
+                given: position,
+                max: self.parse_sheet(sheet_name)?.sections.len(),
+            }),
+        }
+    }
+
+    // The sections at `indices` of the sheet, already picked out as `selected`
+    fn render_selected(
+        &self,
+        sheet_name: &str,
+        indices: &[usize],
+        selected: &[Cow<'_, Section>],
+    ) -> Result<String, CheatError> {
+        let sections = self.displayed_sections(selected.iter().map(AsRef::as_ref));
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                return match sections.as_slice() {
+                    [section] => self.render_json(section),
+                    _ => self.render_json(&sections),
+                };
+            }
+            OutputFormat::Markdown => {
+                let rendered: Vec<String> = indices
+                    .iter()
+                    .zip(&sections)
+                    .map(|(&i, section)| markdown::section(i + 1, section))
+                    .collect();
+                return Ok(self.markdown_output(rendered.join("\n")));
+            }
+            OutputFormat::Html => {
+                let sheet = self.find_sheet(sheet_name)?;
+                let rendered: String = indices
+                    .iter()
+                    .zip(&sections)
+                    .map(|(&i, section)| {
+                        html::section(&sheet.name, i + 1, section, &self.highlighter)
+                    })
+                    .collect();
+                return Ok(self.html_document(&sheet.name, &rendered));
+            }
+            OutputFormat::Text => {}
+        }
+
+        let mut output = String::new();
+        for (position, (&i, section)) in indices.iter().zip(&sections).enumerate() {
+            if position > 0 {
+                self.push_separator(&mut output);
+            }
+            match self.boxed {
+                true => output.push_str(&self.render_boxed(i, section)),
+                false => output.push_str(&self.highlight_code(&section.content)),
+            }
+        }
+        Ok(output)
+    }
+
+    // A section's body, highlighted, in a box with its number and title in
+    // the top border. The box is as wide as the widest line, up to the width.
+    fn render_boxed(&self, index: usize, section: &Section) -> String {
+        let number = match self.numbering {
+            Numbering::Position => index + 1,
+            Numbering::Authored => section.number as usize,
+        };
+        let title = format!("{}. {}", number, section.title);
+        let format_border = |border: &str| self.highlighter.format_line_number(border);
+        let format_title = |title: &str| self.highlighter.format_header(title, true);
+        layout::boxed(
+            // Wrapped inside the borders
+            &self.highlight_code_within(
+                section.body(),
+                self.wrap_width().map(|width| width.saturating_sub(4)),
+            ),
+            &title,
+            self.width.unwrap_or(layout::DEFAULT_WIDTH),
+            &format_border,
+            &format_title,
+        )
+    }
+
+    // A lone positional section number picks its section without parsing the
+    // rest of the sheet; None when anything else picks the sections, or the
+    // number is past the end (which the full resolve reports)
+    fn quick_section(
+        &self,
+        sheet_name: &str,
+        section_spec: &str,
+    ) -> Result<Option<(usize, Cow<'_, Section>)>, CheatError> {
+        let index = match section_spec.trim().parse::<usize>() {
+            Ok(number) if number > 0 && self.numbering == Numbering::Position => number - 1,
+            _ => return Ok(None),
+        };
+        let section = self.find_sheet(sheet_name)?.section(index)?;
+        Ok(section.map(|section| (index, section)))
+    }
+
+    // Just the function or class `name` from the selected sections, taken
+    // from the first of them that defines it
+    pub fn render_extract(
+        &self,
+        sheet_name: &str,
+        section_number: &str,
+        name: &str,
+    ) -> Result<String, CheatError> {
+        let cheat_sheet = self.parse_sheet(sheet_name)?;
+        let indices = self.resolve(section_number, &cheat_sheet.sections)?;
+        let code = indices
+            .iter()
+            .find_map(|&i| cheat_sheet.sections[i].definition(name))
+            .ok_or_else(|| CheatError::DefinitionNotFound(name.to_string()))?;
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => self.render_json(&code),
+            OutputFormat::Markdown => Ok(format!("```python\n{}```\n", code)),
+            OutputFormat::Html => {
+                let body = format!(
+                    "<pre><code>{}</code></pre>\n",
+                    self.highlighter.highlight_html(code.trim_end())
+                );
+                Ok(self.html_document(name, &body))
+            }
+            OutputFormat::Text => Ok(self.highlight_code(&code)),
         }
     }
 
-    pub fn show_section(
+    // Only the titles of the selected sections, one per line
+    pub fn render_section_title(
         &self,
         sheet_name: &str,
         section_number: &str,
-    ) -> Result<(), Box<dyn Error>> {
-        let content = self
-            .get_sheet_content(sheet_name)
-            .ok_or_else(|| format!("Could not find sheet {}", sheet_name))?;
+    ) -> Result<String, CheatError> {
+        let cheat_sheet = self.parse_sheet(sheet_name)?;
+        let indices = self.resolve(section_number, &cheat_sheet.sections)?;
+        let titles: Vec<&str> = indices
+            .iter()
+            .map(|&i| cheat_sheet.sections[i].title.as_str())
+            .collect();
 
-        let cheat_sheet = CheatSheet::parse(content)?;
-        let section_idx = section_number
-            .parse::<usize>()
-            .map_err(|_| "Section number must be a positive integer")?;
+        if self.format.is_json() {
+            return match titles.as_slice() {
+                [title] => self.render_json(title),
+                _ => self.render_json(&titles),
+            };
+        }
+        Ok(titles.iter().map(|title| format!("{}\n", title)).collect())
+    }
 
-        if section_idx == 0 || section_idx > cheat_sheet.sections.len() {
-            return Err("Invalid section number".into());
+    pub fn render_full_sheet(&self, sheet_name: &str) -> Result<String, CheatError> {
+        let sheet = self.find_sheet(sheet_name)?;
+        // Only text is reversed, as the other formats number sections by
+        // where they appear
+        if self.transforms_sections()
+            || (self.format == OutputFormat::Text
+                && (self.reverse || self.separator.is_some() || self.boxed))
+        {
+            return self.render_full_sheet_sections(sheet);
         }
 
-        print!(
-            "{}",
-            self.highlighter
-                .highlight(&cheat_sheet.sections[section_idx - 1].content)
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                return self.render_json(sheet.cheat_sheet()?)
+            }
+            OutputFormat::Markdown => {
+                let cheat_sheet = sheet.cheat_sheet()?;
+                return Ok(
+                    self.markdown_output(markdown::sheet(&sheet.name, &cheat_sheet.sections))
+                );
+            }
+            OutputFormat::Html => {
+                let cheat_sheet = sheet.cheat_sheet()?;
+                let body = html::sheet(&sheet.name, &cheat_sheet.sections, &self.highlighter);
+                return Ok(self.html_document(&sheet.name, &body));
+            }
+            OutputFormat::Text => {}
+        }
+
+        Ok(format!("{}\n", self.highlight_code(&sheet.content)))
+    }
+
+    // With --code-only or --max-bytes a sheet is rebuilt from its displayed
+    // sections, so text outside any section is left out
+    fn render_full_sheet_sections(&self, sheet: &LoadedSheet) -> Result<String, CheatError> {
+        let cheat_sheet = CheatSheet {
+            sections: self.sheet_sections(sheet)?.into_owned(),
+        };
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => self.render_json(&cheat_sheet),
+            OutputFormat::Markdown => {
+                Ok(self.markdown_output(markdown::sheet(&sheet.name, &cheat_sheet.sections)))
+            }
+            OutputFormat::Html => {
+                let body = html::sheet(&sheet.name, &cheat_sheet.sections, &self.highlighter);
+                Ok(self.html_document(&sheet.name, &body))
+            }
+            OutputFormat::Text if self.separator.is_some() || self.boxed => {
+                let mut sections: Vec<(usize, &Section)> =
+                    cheat_sheet.sections.iter().enumerate().collect();
+                if self.reverse {
+                    sections.reverse();
+                }
+                let mut output = String::new();
+                for (position, &(i, section)) in sections.iter().enumerate() {
+                    if position > 0 {
+                        self.push_separator(&mut output);
+                    }
+                    match self.boxed {
+                        true => output.push_str(&self.render_boxed(i, section)),
+                        false => output.push_str(&self.highlight_code(&section.content)),
+                    }
+                }
+                Ok(output)
+            }
+            OutputFormat::Text => {
+                let mut code: Vec<&str> = cheat_sheet
+                    .sections
+                    .iter()
+                    .map(|section| section.content.as_str())
+                    .collect();
+                if self.reverse {
+                    code.reverse();
+                }
+                Ok(self.highlight_code(&code.join("\n")))
+            }
+        }
+    }
+
+    // (sheet, number, section) for every section of one sheet or of all of
+    // them; NoSections if that comes to none
+    fn numbered_sections(
+        &self,
+        sheet_name: Option<&str>,
+    ) -> Result<Vec<(&str, usize, &Section)>, CheatError> {
+        let sheets = match sheet_name {
+            Some(name) => vec![self.find_sheet(name)?],
+            None => self.sheets.iter().collect(),
+        };
+        let mut sections = Vec::new();
+        for sheet in sheets {
+            for (i, section) in sheet.cheat_sheet()?.sections.iter().enumerate() {
+                sections.push((sheet.name.as_ref(), i + 1, section));
+            }
+        }
+        if sections.is_empty() {
+            return Err(CheatError::NoSections);
+        }
+        Ok(sections)
+    }
+
+    // A uniformly random section across all sheets (or one sheet), under a
+    // header naming it so it can be looked up again
+    pub fn render_random(&self, sheet_name: Option<&str>) -> Result<String, CheatError> {
+        let choices = self.numbered_sections(sheet_name)?;
+        let (sheet, number, section) = choices[fastrand::usize(..choices.len())];
+        self.render_located(sheet, number, section)
+    }
+
+    // One section under a header naming its sheet and number, so it can be
+    // looked up again directly
+    fn render_located(
+        &self,
+        sheet: &str,
+        number: usize,
+        section: &Section,
+    ) -> Result<String, CheatError> {
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => self.render_json(&SearchHit {
+                sheet,
+                section: number,
+                title: &section.title,
+                content: &section.content,
+            }),
+            OutputFormat::Markdown => Ok(format!(
+                "# {}\n\n{}",
+                sheet,
+                self.render_section_at(sheet, number)?
+            )),
+            OutputFormat::Html => self.render_section_at(sheet, number),
+            OutputFormat::Text => {
+                let mut output = String::new();
+                self.render_title(
+                    &mut output,
+                    &format!("{} {}. {}", sheet, number, section.title),
+                );
+                output.push_str(&self.render_section_at(sheet, number)?);
+                Ok(output)
+            }
+        }
+    }
+
+    // Every section of every sheet as one flat `Sheet / N. Title` list
+    // With `offset` and `limit` just that slice of the list, which past the
+    // end is empty. The HTML form is the overview, which isn't sliced.
+    pub fn render_toc(&self, offset: usize, limit: Option<usize>) -> Result<String, CheatError> {
+        let all_sections = self.numbered_sections(None)?;
+        let sections = page(&all_sections, offset, limit);
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                let entries: Vec<TocEntry> = sections
+                    .iter()
+                    .map(|(sheet, number, section)| TocEntry {
+                        sheet,
+                        section: *number,
+                        title: &section.title,
+                    })
+                    .collect();
+                self.render_json(&entries)
+            }
+            // Anchors count repeated headings from the top, so the whole
+            // table is rendered before it's sliced
+            OutputFormat::Markdown => {
+                let toc = markdown::toc(&all_sections);
+                let (heading, entries) = toc.split_once("\n\n").unwrap_or((&toc, ""));
+                let entries: Vec<&str> = entries.split_inclusive('\n').collect();
+                Ok(format!(
+                    "{}\n\n{}",
+                    heading,
+                    page(&entries, offset, limit).concat()
+                ))
+            }
+            // The same lists as the HTML overview
+            OutputFormat::Html => self.render_available_sheets(&[]),
+            OutputFormat::Text => Ok(sections
+                .iter()
+                .map(|(sheet, number, section)| {
+                    format!("{} / {}. {}\n", sheet, number, section.title)
+                })
+                .collect()),
+        }
+    }
+
+    // Section `number` of every sheet that has one, each under a header naming
+    // its sheet, stacked or (with a columns width) side by side
+    pub fn render_compare(&self, number: usize) -> Result<String, CheatError> {
+        let sections: Vec<(&str, usize, &Section)> = self
+            .numbered_sections(None)?
+            .into_iter()
+            .filter(|(_, n, _)| *n == number)
+            .collect();
+        if sections.is_empty() {
+            let max = self
+                .sheets
+                .iter()
+                .map(|sheet| sheet.cheat_sheet().map_or(0, |sheet| sheet.sections.len()))
+                .max()
+                .unwrap_or(0);
+            return Err(CheatError::SectionOutOfRange { given: number, max });
+        }
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                let entries: Vec<SearchHit> = sections
+                    .iter()
+                    .map(|(sheet, number, section)| SearchHit {
+                        sheet,
+                        section: *number,
+                        title: &section.title,
+                        content: &section.content,
+                    })
+                    .collect();
+                return self.render_json(&entries);
+            }
+            OutputFormat::Markdown => {
+                let rendered: Vec<String> = sections
+                    .iter()
+                    .map(|(sheet, number, section)| {
+                        format!("# {}\n\n{}", sheet, markdown::section(*number, section))
+                    })
+                    .collect();
+                return Ok(self.markdown_output(rendered.join("\n")));
+            }
+            OutputFormat::Html => {
+                let rendered: String = sections
+                    .iter()
+                    .map(|(sheet, number, section)| {
+                        format!(
+                            "<h1>{}</h1>\n{}",
+                            html::escape(sheet),
+                            html::section(sheet, *number, section, &self.highlighter)
+                        )
+                    })
+                    .collect();
+                let title = format!("Section {} of every sheet", number);
+                return Ok(self.html_document(&title, &rendered));
+            }
+            OutputFormat::Text => {}
+        }
+
+        let blocks = sections
+            .iter()
+            .map(|(sheet, number, section)| self.render_located(sheet, *number, section))
+            .collect::<Result<Vec<_>, _>>()?;
+        let columns = self
+            .columns_width()
+            .map_or(1, |width| layout::column_count(&blocks, width));
+        let mut output = String::new();
+        for (i, row) in blocks.chunks(columns).enumerate() {
+            if i > 0 {
+                self.push_separator(&mut output);
+            }
+            output.push_str(&layout::side_by_side(row));
+        }
+        Ok(output)
+    }
+
+    // Sections of any sheet whose title fuzzily matches `query`, keeping only
+    // the best-scoring ones
+    pub fn find_sections(&self, query: &str) -> Result<Vec<(&str, usize, &Section)>, CheatError> {
+        if query.trim().is_empty() {
+            return Err(CheatError::EmptySearchTerm);
+        }
+        let mut scored = Vec::new();
+        for (sheet, number, section) in self.numbered_sections(None)? {
+            if let Some(score) = fuzzy::word_score(query, &section.title) {
+                scored.push((score, (sheet, number, section)));
+            }
+        }
+        let best = scored.iter().map(|(score, _)| *score).min();
+        Ok(scored
+            .into_iter()
+            .filter(|(score, _)| Some(*score) == best)
+            .map(|(_, found)| found)
+            .collect())
+    }
+
+    // The section whose title best matches `query`, or a list of the
+    // candidates when several match equally well
+    pub fn render_find(&self, query: &str) -> Result<String, CheatError> {
+        let found = self.find_sections(query)?;
+        match found.as_slice() {
+            [] => Err(CheatError::SectionNotFound(query.trim().to_string())),
+            [(sheet, number, section)] => self.render_located(sheet, *number, section),
+            candidates => {
+                if self.format.is_json() {
+                    let entries: Vec<SearchHit> = candidates
+                        .iter()
+                        .map(|(sheet, number, section)| SearchHit {
+                            sheet,
+                            section: *number,
+                            title: &section.title,
+                            content: &section.content,
+                        })
+                        .collect();
+                    return self.render_json(&entries);
+                }
+                let mut output = format!("Several sections match \"{}\":\n", query.trim());
+                for (sheet, number, section) in candidates {
+                    output.push_str(&format!("  {} {}. {}\n", sheet, number, section.title));
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    // Section and line counts for one sheet, or for every sheet
+    pub fn render_stats(&self, sheet_name: Option<&str>) -> Result<String, CheatError> {
+        let sheets = match sheet_name {
+            Some(name) => vec![self.find_sheet(name)?],
+            None => self.sheets.iter().collect(),
+        };
+        let stats = sheets
+            .iter()
+            .map(|sheet| {
+                Ok(SheetStats::collect(
+                    &sheet.name,
+                    &sheet.content,
+                    sheet.cheat_sheet()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, CheatError>>()?;
+
+        if self.format.is_json() {
+            return self.render_json(&stats);
+        }
+        Ok(stats::table(&stats))
+    }
+
+    // Problems `lint::check` finds in every section of one sheet or all of
+    // them, in sheet and line order
+    pub fn lint(&self, sheet_name: Option<&str>) -> Result<Vec<LintEntry<'_>>, CheatError> {
+        let mut entries = Vec::new();
+        for (sheet, number, section) in self.numbered_sections(sheet_name)? {
+            for problem in lint::check(section) {
+                entries.push(LintEntry {
+                    sheet,
+                    section: number,
+                    title: &section.title,
+                    line: problem.line,
+                    message: problem.message,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn render_lint(&self, sheet_name: Option<&str>) -> Result<String, CheatError> {
+        self.render_lint_entries(&self.lint(sheet_name)?)
+    }
+
+    fn render_lint_entries(&self, entries: &[LintEntry]) -> Result<String, CheatError> {
+        if self.format.is_json() {
+            return self.render_json(entries);
+        }
+        if entries.is_empty() {
+            return Ok("No problems found\n".to_string());
+        }
+        Ok(entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {}. {}, line {}: {}\n",
+                    entry.sheet, entry.section, entry.title, entry.line, entry.message
+                )
+            })
+            .collect())
+    }
+
+    // Section titles present in one sheet but not the other
+    pub fn render_diff(&self, left: &str, right: &str) -> Result<String, CheatError> {
+        let left = self.find_sheet(left)?;
+        let right = self.find_sheet(right)?;
+        let diff = SheetDiff::compare(
+            &left.name,
+            left.cheat_sheet()?,
+            &right.name,
+            right.cheat_sheet()?,
         );
+
+        if self.format.is_json() {
+            return self.render_json(&diff);
+        }
+        Ok(diff.render())
+    }
+
+    // Every sheet in full, one after another under a `==> Name <==` header
+    pub fn render_all(&self) -> Result<String, CheatError> {
+        let sheets = self
+            .sheets
+            .iter()
+            .map(|sheet| Ok((sheet.name.as_ref(), self.sheet_sections(sheet)?)))
+            .collect::<Result<Vec<_>, CheatError>>()?;
+
+        match self.format {
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                let entries: Vec<SheetEntry> = sheets
+                    .iter()
+                    .map(|(name, sections)| SheetEntry { name, sections })
+                    .collect();
+                self.render_json(&entries)
+            }
+            OutputFormat::Markdown => {
+                let rendered: Vec<String> = sheets
+                    .iter()
+                    .map(|(name, sections)| markdown::sheet(name, sections))
+                    .collect();
+                Ok(self.markdown_output(rendered.join("\n")))
+            }
+            OutputFormat::Html => {
+                let body: String = sheets
+                    .iter()
+                    .map(|(name, sections)| html::sheet(name, sections, &self.highlighter))
+                    .collect();
+                Ok(self.html_document("Python cheat sheets", &body))
+            }
+            OutputFormat::Text => {
+                let mut output = String::new();
+                for (i, (name, _)) in sheets.iter().enumerate() {
+                    if i > 0 {
+                        output.push('\n');
+                    }
+                    self.render_title(&mut output, &format!("==> {} <==", name));
+                    output.push_str(&self.render_full_sheet(name)?);
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    // The sheet's sections as displayed
+    fn sheet_sections<'a>(&self, sheet: &'a LoadedSheet) -> Result<Cow<'a, [Section]>, CheatError> {
+        let sections = &sheet.cheat_sheet()?.sections;
+        Ok(if self.transforms_sections() {
+            Cow::Owned(
+                sections
+                    .iter()
+                    .map(|section| self.displayed(section).into_owned())
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(sections)
+        })
+    }
+
+    // The sheet's source text, bypassing the parser entirely
+    pub fn render_raw(&self, sheet_name: &str) -> Result<String, CheatError> {
+        Ok(self.find_sheet(sheet_name)?.content.to_string())
+    }
+
+    fn search_index(&self) -> Result<&SearchIndex, CheatError> {
+        if let Some(index) = self.index.get() {
+            return Ok(index);
+        }
+        let parsed = self
+            .sheets
+            .iter()
+            .map(|sheet| Ok(sheet.cheat_sheet()?.sections.as_slice()))
+            .collect::<Result<Vec<_>, CheatError>>()?;
+        let index = SearchIndex::build(parsed);
+        Ok(self.index.get_or_init(|| index))
+    }
+
+    // Every section whose title or content contains `term`, ignoring case, as
+    // (sheet name, section number, section) in sheet order
+    pub fn search(&self, term: &str) -> Result<Vec<(String, usize, &Section)>, CheatError> {
+        let term = term.trim();
+        if term.is_empty() {
+            return Err(CheatError::EmptySearchTerm);
+        }
+
+        let needle = term.to_lowercase();
+        let mut hits = Vec::new();
+        let candidates = self.search_index()?.candidates(&needle);
+        for (i, sheet) in self.sheets.iter().enumerate() {
+            for (j, section) in sheet.cheat_sheet()?.sections.iter().enumerate() {
+                let is_candidate = candidates
+                    .as_ref()
+                    .is_none_or(|candidates| candidates.contains(&(i, j)));
+                if is_candidate && section.matches(&needle) {
+                    hits.push((sheet.name.to_string(), j + 1, section));
+                }
+            }
+        }
+        Ok(hits)
+    }
+
+    // Every section whose title or content matches `pattern`, as
+    // (sheet name, section number, section) in sheet order. Case-sensitive
+    // unless the pattern says otherwise, e.g. with `(?i)`.
+    pub fn search_regex(
+        &self,
+        pattern: &str,
+    ) -> Result<Vec<(String, usize, &Section)>, CheatError> {
+        let regex = Regex::new(pattern).map_err(CheatError::InvalidRegex)?;
+        let mut hits = Vec::new();
+        for sheet in &self.sheets {
+            for (i, section) in sheet.cheat_sheet()?.sections.iter().enumerate() {
+                if regex.is_match(&section.title) || regex.is_match(&section.content) {
+                    hits.push((sheet.name.to_string(), i + 1, section));
+                }
+            }
+        }
+        Ok(hits)
+    }
+
+    pub fn render_search(&self, term: &str) -> Result<String, CheatError> {
+        let term = term.trim();
+        let hits = self.search(term)?;
+        self.render_hits(hits, &format!("\"{}\"", term), &|text| {
+            find_matches(text, term)
+        })
+    }
+
+    pub fn render_regex_search(&self, pattern: &str) -> Result<String, CheatError> {
+        let hits = self.search_regex(pattern)?;
+        let regex = Regex::new(pattern).map_err(CheatError::InvalidRegex)?;
+        self.render_hits(hits, &format!("/{}/", pattern), &|text| {
+            regex
+                .find_iter(text)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect()
+        })
+    }
+
+    // Search results under a header per hit, with the spans `find` reports
+    // emphasized. `query` names the search in messages.
+    fn render_hits(
+        &self,
+        hits: Vec<(String, usize, &Section)>,
+        query: &str,
+        find: &dyn Fn(&str) -> Vec<Range<usize>>,
+    ) -> Result<String, CheatError> {
+        if self.count_only {
+            return self.render_hit_counts(&hits);
+        }
+
+        let hits: Vec<(String, usize, Cow<Section>)> = hits
+            .into_iter()
+            .map(|(sheet, number, section)| (sheet, number, self.displayed(section)))
+            .collect();
+
+        match self.format {
+            // Enough to look each hit up, a line apiece for consumers that
+            // process results as they arrive
+            OutputFormat::JsonLines => {
+                let entries: Vec<TocEntry> = hits
+                    .iter()
+                    .map(|(sheet, number, section)| TocEntry {
+                        sheet,
+                        section: *number,
+                        title: &section.title,
+                    })
+                    .collect();
+                return self.render_json(&entries);
+            }
+            OutputFormat::Json => {
+                let entries: Vec<SearchHit> = hits
+                    .iter()
+                    .map(|(sheet, number, section)| SearchHit {
+                        sheet,
+                        section: *number,
+                        title: &section.title,
+                        content: &section.content,
+                    })
+                    .collect();
+                return self.render_json(&entries);
+            }
+            OutputFormat::Markdown => {
+                let rendered: Vec<String> = hits
+                    .iter()
+                    .map(|(sheet, number, section)| {
+                        format!("# {}\n\n{}", sheet, markdown::section(*number, section))
+                    })
+                    .collect();
+                return Ok(self.markdown_output(rendered.join("\n")));
+            }
+            OutputFormat::Html => {
+                let rendered: String = hits
+                    .iter()
+                    .map(|(sheet, number, section)| {
+                        format!(
+                            "<h1>{}</h1>\n{}",
+                            html::escape(sheet),
+                            html::section(sheet, *number, section, &self.highlighter)
+                        )
+                    })
+                    .collect();
+                return Ok(self.html_document(&format!("Search: {}", query), &rendered));
+            }
+            OutputFormat::Text => {}
+        }
+
+        let mut output = String::new();
+        if hits.is_empty() {
+            output.push_str(&format!("No sections match {}\n", query));
+        }
+        for (i, (sheet, number, section)) in hits.iter().enumerate() {
+            let header = format!("{} {}. {}", sheet, number, section.title);
+            match i {
+                0 => output.push('\n'),
+                _ => self.push_separator(&mut output),
+            }
+            self.render_title(&mut output, &header);
+            let marks = find(&section.content);
+            let highlighted = self.highlighter.highlight_marked(&section.content, &marks);
+            match self.search_context {
+                Some(context) => output.push_str(&layout::excerpt(
+                    &highlighted,
+                    &marked_lines(&section.content, &marks),
+                    context,
+                )),
+                None => output.push_str(&highlighted),
+            }
+        }
+        Ok(output)
+    }
+
+    // Matching sections per sheet, every sheet listed even without any, and
+    // the total. Never highlighted.
+    fn render_hit_counts(&self, hits: &[(String, usize, &Section)]) -> Result<String, CheatError> {
+        let sheets: Vec<HitCount> = self
+            .sheets
+            .iter()
+            .map(|sheet| HitCount {
+                sheet: &sheet.name,
+                sections: hits
+                    .iter()
+                    .filter(|(name, _, _)| *name == sheet.name)
+                    .count(),
+            })
+            .collect();
+
+        if self.format.is_json() {
+            return self.render_json(&HitCounts {
+                sheets,
+                total: hits.len(),
+            });
+        }
+        let mut output: String = sheets
+            .iter()
+            .map(|count| format!("{}: {}\n", count.sheet, count.sections))
+            .collect();
+        output.push_str(&format!("Total: {}\n", hits.len()));
+        Ok(output)
+    }
+
+    pub fn show_sheet_names(&self, offset: usize, limit: Option<usize>) -> Result<(), CheatError> {
+        self.emit(&self.render_sheet_names(offset, limit)?)
+    }
+
+    pub fn show_available_sheets(&self, only: &[String]) -> Result<(), CheatError> {
+        self.emit(&self.render_available_sheets(only)?)
+    }
+
+    pub fn show_sheet_outline(&self, sheet_name: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_sheet_outline(sheet_name)?)
+    }
+
+    pub fn show_sheet_tree(&self, sheet_name: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_sheet_tree(sheet_name)?)
+    }
+
+    pub fn show_summary(&self, sheet_name: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_summary(sheet_name)?)
+    }
+
+    pub fn show_section(&self, sheet_name: &str, section_number: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_section(sheet_name, section_number)?)
+    }
+
+    pub fn show_section_at(&self, sheet_name: &str, position: usize) -> Result<(), CheatError> {
+        self.emit(&self.render_section_at(sheet_name, position)?)
+    }
+
+    pub fn show_section_title(
+        &self,
+        sheet_name: &str,
+        section_number: &str,
+    ) -> Result<(), CheatError> {
+        self.emit(&self.render_section_title(sheet_name, section_number)?)
+    }
+
+    pub fn show_extract(
+        &self,
+        sheet_name: &str,
+        section_number: &str,
+        name: &str,
+    ) -> Result<(), CheatError> {
+        self.emit(&self.render_extract(sheet_name, section_number, name)?)
+    }
+
+    // Unpaged plain text is highlighted straight to the output as it's
+    // colored, so big --dir sheets start appearing at once and are never held
+    // highlighted in full
+    pub fn show_full_sheet(&self, sheet_name: &str) -> Result<(), CheatError> {
+        let streams = (!self.pager || self.output.is_some())
+            && self.format == OutputFormat::Text
+            && !self.transforms_sections()
+            && !self.reverse
+            && self.separator.is_none()
+            && !self.boxed
+            && !self.line_numbers
+            && !self.wrap;
+        if !streams {
+            return self.emit(&self.render_full_sheet(sheet_name)?);
+        }
+
+        let sheet = self.find_sheet(sheet_name)?;
+        let code = self.titled_references(&sheet.content);
+        self.stream_highlighted(code.as_bytes().chain(&b"\n"[..]))
+            .map_err(|source| self.output_error(source))?;
+        self.report_written();
+        Ok(())
+    }
+
+    pub fn show_toc(&self, offset: usize, limit: Option<usize>) -> Result<(), CheatError> {
+        self.emit(&self.render_toc(offset, limit)?)
+    }
+
+    pub fn show_compare(&self, number: usize) -> Result<(), CheatError> {
+        self.emit(&self.render_compare(number)?)
+    }
+
+    pub fn show_random(&self, sheet_name: Option<&str>) -> Result<(), CheatError> {
+        self.emit(&self.render_random(sheet_name)?)
+    }
+
+    pub fn show_stats(&self, sheet_name: Option<&str>) -> Result<(), CheatError> {
+        self.emit(&self.render_stats(sheet_name)?)
+    }
+
+    // Reports the problems found, failing if there were any so scripts and
+    // CI can tell
+    pub fn show_lint(&self, sheet_name: Option<&str>) -> Result<(), CheatError> {
+        let entries = self.lint(sheet_name)?;
+        self.emit(&self.render_lint_entries(&entries)?)?;
+        match entries.len() {
+            0 => Ok(()),
+            count => Err(CheatError::LintProblems(count)),
+        }
+    }
+
+    pub fn show_diff(&self, left: &str, right: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_diff(left, right)?)
+    }
+
+    pub fn show_all(&self) -> Result<(), CheatError> {
+        self.emit(&self.render_all()?)
+    }
+
+    pub fn show_raw(&self, sheet_name: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_raw(sheet_name)?)
+    }
+
+    // Highlights any Python file, or standard input for `-`, straight to
+    // standard output as it's read; never paged, so it works in a pipeline
+    pub fn show_highlighted(&self, path: &Path) -> Result<(), CheatError> {
+        let read_error = |source| CheatError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let input: Box<dyn io::BufRead> = if path == Path::new("-") {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(io::BufReader::new(
+                fs::File::open(path).map_err(read_error)?,
+            ))
+        };
+        // Reading and writing fail alike; name the input only for input
+        // errors such as invalid UTF-8
+        self.stream_highlighted(input)
+            .map_err(|source| match source.kind() {
+                io::ErrorKind::InvalidData => read_error(source),
+                _ => self.output_error(source),
+            })?;
+        self.report_written();
         Ok(())
     }
 
-    pub fn show_full_sheet(&self, sheet_name: &str) -> Result<(), Box<dyn Error>> {
-        let content = self
-            .get_sheet_content(sheet_name)
-            .ok_or_else(|| format!("Could not find sheet {}", sheet_name))?;
+    // Highlights `input` a line at a time into the output file or a buffered
+    // lock on stdout
+    fn stream_highlighted(&self, input: impl io::BufRead) -> io::Result<()> {
+        match &self.output {
+            Some(output) => self
+                .highlighter
+                .highlight_stream(input, io::BufWriter::new(fs::File::create(output)?)),
+            None => self
+                .highlighter
+                .highlight_stream(input, io::BufWriter::new(io::stdout().lock())),
+        }
+    }
+
+    fn report_written(&self) {
+        if let Some(output) = &self.output {
+            eprintln!("Wrote {}", output.display());
+        }
+    }
+
+    // Plain text of the selected sections, or the whole sheet for no section
+    // or 0, plus a label such as "section 3 of Basics" for messages
+    fn plain_selection(
+        &self,
+        sheet_name: &str,
+        section_spec: Option<&str>,
+    ) -> Result<(String, String), CheatError> {
+        let sheet = self.find_sheet(sheet_name)?;
+        let section_spec = section_spec.filter(|spec| *spec != "0");
+        if section_spec.is_none() && !self.transforms_sections() {
+            return Ok((sheet.content.to_string(), sheet.name.to_string()));
+        }
+
+        let cheat_sheet = sheet.cheat_sheet()?;
+        let indices = match section_spec {
+            Some(spec) => self.resolve(spec, &cheat_sheet.sections)?,
+            None => (0..cheat_sheet.sections.len()).collect(),
+        };
+        let sections = self.displayed_sections(indices.iter().map(|&i| &cheat_sheet.sections[i]));
+        let text: Vec<&str> = sections
+            .iter()
+            .map(|section| section.content.as_str())
+            .collect();
+        let label = match (section_spec, indices.as_slice()) {
+            (Some(_), [index]) => format!("section {} of {}", index + 1, sheet.name),
+            (Some(_), [first, .., last])
+                if indices.windows(2).all(|pair| pair[1] == pair[0] + 1) =>
+            {
+                format!("sections {}-{} of {}", first + 1, last + 1, sheet.name)
+            }
+            (Some(_), _) => {
+                let numbers: Vec<String> = indices.iter().map(|i| (i + 1).to_string()).collect();
+                format!("sections {} of {}", numbers.join(", "), sheet.name)
+            }
+            _ => sheet.name.to_string(),
+        };
+        Ok((text.join("\n"), label))
+    }
 
-        println!("{}", self.highlighter.highlight(content));
+    // Copies the selection without escape codes; prints it instead when the
+    // clipboard can't be reached (e.g. no display on a headless machine)
+    pub fn copy_to_clipboard(
+        &self,
+        sheet_name: &str,
+        section_spec: Option<&str>,
+    ) -> Result<(), CheatError> {
+        let (text, label) = self.plain_selection(sheet_name, section_spec)?;
+        match clipboard::copy(&text) {
+            Ok(()) => eprintln!("Copied {} to clipboard", label),
+            Err(e) => {
+                self.warn(&format!(
+                    "Could not access the clipboard ({}); printing instead",
+                    e
+                ));
+                self.emit(&text)?;
+            }
+        }
         Ok(())
     }
 
+    pub fn show_find(&self, query: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_find(query)?)
+    }
+
+    pub fn show_search(&self, term: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_search(term)?)
+    }
+
+    pub fn show_regex_search(&self, pattern: &str) -> Result<(), CheatError> {
+        self.emit(&self.render_regex_search(pattern)?)
+    }
+
+    // Quizzes the user on the sheet's section titles, in random order, over
+    // stdin and stdout
+    pub fn quiz(&self, sheet_name: &str) -> Result<(), CheatError> {
+        let sections = &self.parse_sheet(sheet_name)?.sections;
+        if sections.is_empty() {
+            return Err(CheatError::NoSections);
+        }
+        let mut questions: Vec<Question> = sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| Question {
+                number: i + 1,
+                title: &section.title,
+                body: self.highlight_code(section.body()),
+            })
+            .collect();
+        fastrand::shuffle(&mut questions);
+        quiz::run(&questions, io::stdin().lock(), io::stdout().lock()).map_err(CheatError::Terminal)
+    }
+
+    pub fn browse(&self, sheet_name: Option<&str>) -> Result<(), CheatError> {
+        tui::browse(self, sheet_name)
+    }
+
+    // Offers every section (of one sheet, or all of them) as a `Sheet N. Title`
+    // line in fzf and shows the one picked; picking nothing prints nothing
+    pub fn pick_with_fzf(&self, sheet_name: Option<&str>) -> Result<(), CheatError> {
+        let choices = self.numbered_sections(sheet_name)?;
+        let entries: Vec<String> = choices
+            .iter()
+            .map(|(sheet, number, section)| format!("{} {}. {}", sheet, number, section.title))
+            .collect();
+        let Some(choice) = fzf::pick(&entries).map_err(CheatError::Fzf)? else {
+            return Ok(());
+        };
+        match entries.iter().position(|entry| *entry == choice) {
+            Some(i) => {
+                let (sheet, number, _) = choices[i];
+                self.show_section_at(sheet, number)
+            }
+            None => Ok(()),
+        }
+    }
+
     pub fn format_error(&self, error: &str) -> String {
         self.highlighter.format_error(error)
     }
 }
+
+// The items from `offset` on, at most `limit` of them; none past the end
+fn page<T>(items: &[T], offset: usize, limit: Option<usize>) -> &[T] {
+    let start = offset.min(items.len());
+    let end = limit.map_or(items.len(), |limit| {
+        start.saturating_add(limit).min(items.len())
+    });
+    &items[start..end]
+}
+
+fn stdout_error(source: io::Error) -> CheatError {
+    CheatError::Write {
+        path: PathBuf::from("standard output"),
+        source,
+    }
+}
+
+// Indices of the lines of `content` overlapping any of the byte ranges in
+// `marks`, which are in order
+fn marked_lines(content: &str, marks: &[Range<usize>]) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, line) in content.split('\n').enumerate() {
+        let end = start + line.len();
+        if marks
+            .iter()
+            .any(|mark| mark.start <= end && mark.end > start)
+        {
+            lines.push(i);
+        }
+        start = end + 1;
+    }
+    lines
+}
+
+impl Default for CheatSheetManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheet_without_markers_has_no_sections() {
+        let mut manager = CheatSheetManager::with_color(false);
+        manager.add_sheet("Empty", "x = 1\nprint(x)\n");
+
+        assert!(manager.parse_sheet("Empty").unwrap().sections.is_empty());
+        assert_eq!(
+            manager.render_sheet_tree("Empty").unwrap(),
+            "(no sections)\n"
+        );
+        assert!(matches!(
+            manager.render_section("Empty", "1"),
+            Err(CheatError::NoSections)
+        ));
+    }
+}
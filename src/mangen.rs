@@ -0,0 +1,12 @@
+use crate::cli::Cli;
+use clap_mangen::Man;
+
+// The man page for `py_cheat`, with the `--help` text's sheet list and exit
+// statuses as its EXTRA section
+pub fn generate() -> String {
+    let mut page = Vec::new();
+    Man::new(Cli::command_with_sheet_list())
+        .render(&mut page)
+        .expect("writing to memory can't fail");
+    String::from_utf8(page).expect("clap help text is UTF-8")
+}
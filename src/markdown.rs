@@ -0,0 +1,82 @@
+use crate::cheatsheet::Section;
+use std::collections::HashMap;
+
+// Renders a section as a `## N. Title` heading followed by its body in a
+// fenced python block
+pub fn section(number: usize, section: &Section) -> String {
+    format!(
+        "## {}. {}\n\n```python\n{}\n```\n",
+        number,
+        section.title,
+        section.body().trim_matches('\n')
+    )
+}
+
+// Renders a sheet's sections as a numbered Markdown list under its name
+pub fn outline(sheet_name: &str, sections: &[Section]) -> String {
+    let mut output = format!("# {}\n\n", sheet_name);
+    for (i, section) in sections.iter().enumerate() {
+        output.push_str(&format!("{}. {}\n", i + 1, section.title));
+    }
+    output
+}
+
+// Renders every section of a sheet under a top-level sheet heading
+pub fn sheet(sheet_name: &str, sections: &[Section]) -> String {
+    let mut output = format!("# {}\n", sheet_name);
+    for (i, item) in sections.iter().enumerate() {
+        output.push('\n');
+        output.push_str(&section(i + 1, item));
+    }
+    output
+}
+
+// Renders every (sheet, number, section) as one flat list, each linking to
+// its heading in the `--all --format markdown` document. Anchors follow
+// GitHub's heading slugs, which number repeats of a slug in page order.
+pub fn toc(sections: &[(&str, usize, &Section)]) -> String {
+    let mut output = String::from("# Table of contents\n\n");
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut anchor = |heading: &str| {
+        let slug = slug(heading);
+        let count = seen.entry(slug.clone()).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => slug,
+            n => format!("{}-{}", slug, n - 1),
+        }
+    };
+    // The table's own heading comes first on a page it's prepended to
+    anchor("Table of contents");
+
+    let mut current_sheet = None;
+    for (sheet_name, number, section) in sections {
+        if current_sheet != Some(*sheet_name) {
+            anchor(sheet_name);
+            current_sheet = Some(*sheet_name);
+        }
+        let heading = format!("{}. {}", number, section.title);
+        output.push_str(&format!(
+            "- [{} / {}](#{})\n",
+            sheet_name,
+            heading,
+            anchor(&heading)
+        ));
+    }
+    output
+}
+
+// GitHub's anchor for a heading: lowercased, spaces as `-`, and punctuation
+// other than `-` and `_` dropped
+fn slug(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,53 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use terminal_size::{terminal_size, Height};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+// Prints `output` through $PAGER when stdout is a terminal and the output is
+// taller than it. Falls back to printing directly if the pager can't be started.
+pub fn print_paged(output: &str) -> io::Result<()> {
+    if io::stdout().is_terminal() && exceeds_terminal_height(output) && spawn_pager(output).is_ok()
+    {
+        return Ok(());
+    }
+    print(output)
+}
+
+// Writes `output` to stdout, returning the error instead of panicking as
+// `print!` does when the reader has gone away (`py_cheat Basics | head`)
+pub fn print(output: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(output.as_bytes())?;
+    stdout.flush()
+}
+
+fn exceeds_terminal_height(output: &str) -> bool {
+    terminal_size().is_some_and(|(_, Height(height))| output.lines().count() > height as usize)
+}
+
+fn spawn_pager(output: &str) -> io::Result<()> {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_PAGER);
+    let mut command = Command::new(program);
+    command.args(parts).stdin(Stdio::piped());
+
+    // Let a bare `less` pass ANSI colors through, as git does
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may quit before reading everything, which isn't an error
+        let _ = stdin.write_all(output.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::fuzzy;
+use std::io::{self, BufRead, Write};
+
+// One section to guess: its body, already highlighted, and what to reveal
+pub struct Question<'a> {
+    pub number: usize,
+    pub title: &'a str,
+    pub body: String,
+}
+
+// Asks each question in turn: shows the body, reads a guess and reveals the
+// title. A guess is right when every word of it matches a word of the title
+// the way `find` matches them. Ends after the last question, at end of input
+// or on `q`, printing the score.
+pub fn run(
+    questions: &[Question],
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut asked = 0;
+    let mut right = 0;
+    for (i, question) in questions.iter().enumerate() {
+        write!(
+            output,
+            "\nQuestion {} of {}\n\n{}\nWhat is this section about? (q to quit) > ",
+            i + 1,
+            questions.len(),
+            question.body
+        )?;
+        output.flush()?;
+
+        let mut guess = String::new();
+        if input.read_line(&mut guess)? == 0 {
+            writeln!(output)?;
+            break;
+        }
+        let guess = guess.trim();
+        if guess.eq_ignore_ascii_case("q") {
+            break;
+        }
+
+        asked += 1;
+        let verdict = if !guess.is_empty() && fuzzy::word_score(guess, question.title).is_some() {
+            right += 1;
+            "Right"
+        } else {
+            "It was"
+        };
+        writeln!(
+            output,
+            "{}: {}. {}",
+            verdict, question.number, question.title
+        )?;
+    }
+    writeln!(output, "\nScore: {}/{}", right, asked)?;
+    output.flush()
+}
@@ -0,0 +1,22 @@
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+// `[[Sheet#N]]`, where N is anything that selects one section (a number or
+// a title)
+static REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\[\]#]+)#([^\[\]]+)\]\]").expect("valid regex"));
+
+// `text` with each cross-reference replaced by what `resolve` makes of its
+// sheet and section, or left as is where that's None
+pub fn replace<'a>(
+    text: &'a str,
+    mut resolve: impl FnMut(&str, &str) -> Option<String>,
+) -> Cow<'a, str> {
+    if !text.contains("[[") {
+        return Cow::Borrowed(text);
+    }
+    REFERENCE.replace_all(text, |captures: &Captures| {
+        resolve(captures[1].trim(), captures[2].trim()).unwrap_or_else(|| captures[0].to_string())
+    })
+}
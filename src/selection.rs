@@ -0,0 +1,138 @@
+use crate::cheatsheet::Section;
+use crate::error::CheatError;
+
+// Which numbers pick sections: their position in the sheet, or the `N.`
+// written in each section's title line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Numbering {
+    #[default]
+    Position,
+    Authored,
+}
+
+// Resolves a section argument into zero-based indices. Numbers (`3`), `last`,
+// negative numbers counting from the end (`-2`) and ranges (`3-5`, `3-last`)
+// select by number, as does a comma-separated list of them (`1-3,7`), kept
+// in the given order with repeats; anything else is matched
+// case-insensitively against section titles. `last` and `-N` always count
+// positions, whatever the numbering.
+pub fn resolve(
+    section_spec: &str,
+    sections: &[Section],
+    numbering: Numbering,
+) -> Result<Vec<usize>, CheatError> {
+    if sections.is_empty() {
+        return Err(CheatError::NoSections);
+    }
+
+    let is_numeric = section_spec
+        .to_lowercase()
+        .replace("last", "")
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '-' || c == ',' || c.is_whitespace());
+
+    if is_numeric {
+        let mut indices = Vec::new();
+        for part in section_spec.split(',') {
+            indices.extend(resolve_numbers(part, sections, numbering)?);
+        }
+        Ok(indices)
+    } else {
+        resolve_title(section_spec, sections).map(|idx| vec![idx])
+    }
+}
+
+// A position from the start, with `last` standing for the final section. With
+// authored numbering a number is first looked up among the sections' own.
+fn parse_position(
+    text: &str,
+    sections: &[Section],
+    numbering: Numbering,
+) -> Result<Option<usize>, CheatError> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("last") {
+        return Ok(Some(sections.len()));
+    }
+    let Ok(number) = text.parse::<usize>() else {
+        return Ok(None);
+    };
+    match numbering {
+        Numbering::Position => Ok(Some(number)),
+        Numbering::Authored => sections
+            .iter()
+            .position(|section| section.number as usize == number)
+            .map(|i| Some(i + 1))
+            .ok_or(CheatError::NoSectionNumbered(number)),
+    }
+}
+
+fn resolve_numbers(
+    section_spec: &str,
+    sections: &[Section],
+    numbering: Numbering,
+) -> Result<Vec<usize>, CheatError> {
+    let section_count = sections.len();
+    if let Some(from_end) = section_spec.trim().strip_prefix('-') {
+        let given = from_end
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| CheatError::InvalidSectionNumber(section_spec.to_string()))?;
+        if given == 0 || given > section_count {
+            return Err(CheatError::SectionFromEndOutOfRange {
+                given,
+                max: section_count,
+            });
+        }
+        return Ok(vec![section_count - given]);
+    }
+
+    let (start, end) = match section_spec.split_once('-') {
+        Some((start, end)) => {
+            let invalid_range = || CheatError::InvalidSectionRange(section_spec.to_string());
+            let start = parse_position(start, sections, numbering)?.ok_or_else(invalid_range)?;
+            let end = parse_position(end, sections, numbering)?.ok_or_else(invalid_range)?;
+            if start > end {
+                return Err(invalid_range());
+            }
+            (start, end)
+        }
+        None => {
+            let idx = parse_position(section_spec, sections, numbering)?
+                .ok_or_else(|| CheatError::InvalidSectionNumber(section_spec.to_string()))?;
+            (idx, idx)
+        }
+    };
+
+    for given in [start, end] {
+        if given == 0 || given > section_count {
+            return Err(CheatError::SectionOutOfRange {
+                given,
+                max: section_count,
+            });
+        }
+    }
+
+    Ok((start - 1..end).collect())
+}
+
+fn resolve_title(query: &str, sections: &[Section]) -> Result<usize, CheatError> {
+    let needle = query.trim().to_lowercase();
+    let candidates: Vec<usize> = sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| section.title.to_lowercase().contains(&needle))
+        .map(|(i, _)| i)
+        .collect();
+
+    match candidates.as_slice() {
+        [idx] => Ok(*idx),
+        [] => Err(CheatError::SectionNotFound(query.to_string())),
+        _ => Err(CheatError::AmbiguousSection {
+            query: query.to_string(),
+            candidates: candidates
+                .iter()
+                .map(|&i| (i + 1, sections[i].title.clone()))
+                .collect(),
+        }),
+    }
+}
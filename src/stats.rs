@@ -0,0 +1,112 @@
+use crate::cheatsheet::CheatSheet;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct LongestSection {
+    pub number: usize,
+    pub title: String,
+    pub lines: usize,
+}
+
+// Size summary of one sheet. Lines inside sections are either comments
+// (see `Section::comment_lines`, plus the section framing) or code; blank
+// lines count toward neither.
+#[derive(Serialize)]
+pub struct SheetStats {
+    pub name: String,
+    pub sections: usize,
+    pub lines: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub longest_section: Option<LongestSection>,
+}
+
+impl SheetStats {
+    pub fn collect(name: &str, content: &str, cheat_sheet: &CheatSheet) -> Self {
+        let mut code_lines = 0;
+        let mut comment_lines = 0;
+        let mut longest_section: Option<LongestSection> = None;
+
+        for (i, section) in cheat_sheet.sections.iter().enumerate() {
+            let lines = section.content.lines().count();
+            let framing = lines - section.body().lines().count();
+            code_lines += section.code_lines().count();
+            comment_lines += framing + section.comment_lines().count();
+
+            if longest_section
+                .as_ref()
+                .is_none_or(|longest| lines > longest.lines)
+            {
+                longest_section = Some(LongestSection {
+                    number: i + 1,
+                    title: section.title.clone(),
+                    lines,
+                });
+            }
+        }
+
+        Self {
+            name: name.to_string(),
+            sections: cheat_sheet.sections.len(),
+            lines: content.lines().count(),
+            code_lines,
+            comment_lines,
+            longest_section,
+        }
+    }
+}
+
+// Aligned plain-text table with one row per sheet
+pub fn table(stats: &[SheetStats]) -> String {
+    let headers = [
+        "Sheet",
+        "Sections",
+        "Lines",
+        "Code",
+        "Comments",
+        "Longest section",
+    ];
+    let rows: Vec<[String; 6]> = stats
+        .iter()
+        .map(|sheet| {
+            let longest = match &sheet.longest_section {
+                Some(longest) => format!(
+                    "{}. {} ({} lines)",
+                    longest.number, longest.title, longest.lines
+                ),
+                None => "-".to_string(),
+            };
+            [
+                sheet.name.clone(),
+                sheet.sections.to_string(),
+                sheet.lines.to_string(),
+                sheet.code_lines.to_string(),
+                sheet.comment_lines.to_string(),
+                longest,
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: [&str; 6]| {
+        // Name left-aligned, counts right-aligned, the last column unpadded
+        let mut line = format!("{:<w$}", cells[0], w = widths[0]);
+        for (cell, width) in cells[1..5].iter().zip(&widths[1..5]) {
+            line.push_str(&format!("  {:>w$}", cell, w = width));
+        }
+        line.push_str(&format!("  {}\n", cells[5]));
+        line
+    };
+
+    let mut output = format_row(headers);
+    for row in &rows {
+        output.push_str(&format_row(row.each_ref().map(String::as_str)));
+    }
+    output
+}
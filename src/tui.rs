@@ -0,0 +1,262 @@
+use crate::error::CheatError;
+use crate::layout;
+use crate::manager::CheatSheetManager;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, IsTerminal, Write};
+
+const SIDEBAR_WIDTH: usize = 30;
+const HELP: &str = "↑/↓ select  Enter open  ←/Esc back  PgUp/PgDn scroll  q quit";
+
+// Restores the terminal however the browser exits, including on errors
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen, Hide) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e);
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Sidebar entries: the sheet list, or one sheet's sections once drilled in
+struct Browser<'a> {
+    manager: &'a CheatSheetManager,
+    sheets: Vec<&'a str>,
+    sheet: Option<usize>,
+    entries: Vec<String>,
+    selected: usize,
+    // Sheet-list selection to return to when backing out of a sheet
+    parent_selected: usize,
+    content: Vec<String>,
+    scroll: usize,
+}
+
+impl<'a> Browser<'a> {
+    fn new(manager: &'a CheatSheetManager) -> Self {
+        let mut browser = Self {
+            manager,
+            sheets: manager.sheet_names().collect(),
+            sheet: None,
+            entries: Vec::new(),
+            selected: 0,
+            parent_selected: 0,
+            content: Vec::new(),
+            scroll: 0,
+        };
+        browser.show_sheet_list();
+        browser
+    }
+
+    fn show_sheet_list(&mut self) {
+        self.sheet = None;
+        self.entries = self.sheets.iter().map(|name| name.to_string()).collect();
+        self.selected = self.parent_selected;
+        self.load_content();
+    }
+
+    fn open_sheet(&mut self, index: usize) {
+        let sections = match self.manager.parse_sheet(self.sheets[index]) {
+            Ok(cheat_sheet) => &cheat_sheet.sections,
+            Err(_) => return,
+        };
+        self.parent_selected = index;
+        self.sheet = Some(index);
+        self.entries = sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| format!("{}. {}", i + 1, section.title))
+            .collect();
+        self.selected = 0;
+        self.load_content();
+    }
+
+    // Fills the main pane for the current selection: a sheet's outline in the
+    // sheet list, the highlighted section once inside a sheet
+    fn load_content(&mut self) {
+        self.scroll = 0;
+        self.content = match self.sheet {
+            None => {
+                let name = self.sheets.get(self.selected).copied().unwrap_or_default();
+                match self.manager.parse_sheet(name) {
+                    Ok(cheat_sheet) => cheat_sheet
+                        .sections
+                        .iter()
+                        .enumerate()
+                        .map(|(i, section)| format!("{}. {}", i + 1, section.title))
+                        .collect(),
+                    Err(e) => vec![e.to_string()],
+                }
+            }
+            Some(sheet) => match self.manager.parse_sheet(self.sheets[sheet]) {
+                Ok(cheat_sheet) => cheat_sheet
+                    .sections
+                    .get(self.selected)
+                    .map(|section| {
+                        self.manager
+                            .highlight_code(&section.content)
+                            .lines()
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                Err(e) => vec![e.to_string()],
+            },
+        };
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.entries.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.load_content();
+        }
+    }
+
+    fn scroll_by(&mut self, rows: isize, page: usize) {
+        let max = self.content.len().saturating_sub(page);
+        self.scroll = self.scroll.saturating_add_signed(rows).min(max);
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let (columns, rows) = (columns as usize, rows as usize);
+        let body_rows = rows.saturating_sub(1);
+        let sidebar_width = SIDEBAR_WIDTH.min(columns / 2);
+        let pane_width = columns.saturating_sub(sidebar_width + 1);
+
+        // Keep the selected entry on screen in long section lists
+        let first_entry = (self.selected + 1).saturating_sub(body_rows);
+
+        queue!(out, Clear(ClearType::All))?;
+        for row in 0..body_rows {
+            queue!(out, MoveTo(0, row as u16))?;
+
+            let index = first_entry + row;
+            if let Some(entry) = self.entries.get(index) {
+                let label: String = entry
+                    .chars()
+                    .take(sidebar_width.saturating_sub(1))
+                    .collect();
+                let label = format!(" {:<width$}", label, width = sidebar_width - 1);
+                if index == self.selected {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reverse),
+                        Print(label),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                } else {
+                    queue!(out, Print(label))?;
+                }
+            } else {
+                queue!(out, Print(" ".repeat(sidebar_width)))?;
+            }
+            queue!(out, Print("│"))?;
+
+            if let Some(line) = self.content.get(self.scroll + row) {
+                queue!(
+                    out,
+                    Print(layout::truncate(line, pane_width)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            }
+        }
+
+        let title = match self.sheet {
+            Some(sheet) => format!(" {} │ {}", self.sheets[sheet], HELP),
+            None => format!(" {}", HELP),
+        };
+        let title: String = title.chars().take(columns).collect();
+        queue!(
+            out,
+            MoveTo(0, body_rows as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{:<columns$}", title)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+
+    // Handles one key press; false once the user asked to quit
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> io::Result<bool> {
+        let page = (terminal::size()?.1 as usize).saturating_sub(1).max(1);
+        match code {
+            KeyCode::Char('q') => return Ok(false),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l')
+                if self.sheet.is_none() && !self.sheets.is_empty() =>
+            {
+                self.open_sheet(self.selected)
+            }
+            KeyCode::Esc | KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
+                if self.sheet.is_some() {
+                    self.show_sheet_list();
+                } else if code == KeyCode::Esc {
+                    return Ok(false);
+                }
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(page as isize, page),
+            KeyCode::PageUp => self.scroll_by(-(page as isize), page),
+            _ => {}
+        }
+        Ok(true)
+    }
+}
+
+// Interactive browser: sheets (then a sheet's sections) in a sidebar, the
+// selected entry's highlighted content in the main pane
+pub fn browse(manager: &CheatSheetManager, sheet_name: Option<&str>) -> Result<(), CheatError> {
+    if !io::stdout().is_terminal() {
+        return Err(CheatError::Terminal(io::Error::other(
+            "--tui needs an interactive terminal",
+        )));
+    }
+
+    let mut browser = Browser::new(manager);
+    if let Some(name) = sheet_name {
+        // Same lookup (and error) as the plain CLI before taking over the screen
+        manager.parse_sheet(name)?;
+        if let Some(index) = browser
+            .sheets
+            .iter()
+            .position(|sheet| sheet.eq_ignore_ascii_case(name))
+        {
+            browser.open_sheet(index);
+        }
+    }
+
+    let _guard = TerminalGuard::enter().map_err(CheatError::Terminal)?;
+    let mut out = io::stdout();
+    loop {
+        browser.draw(&mut out).map_err(CheatError::Terminal)?;
+        if let Event::Key(key) = event::read().map_err(CheatError::Terminal)? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if !browser
+                .handle_key(key.code, key.modifiers)
+                .map_err(CheatError::Terminal)?
+            {
+                return Ok(());
+            }
+        }
+    }
+}
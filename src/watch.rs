@@ -0,0 +1,61 @@
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use py_cheat::CheatError;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Editors often save in several steps (truncate, write, rename), so changes
+// arriving this close together are redrawn once
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+// Clears the screen and calls `render`, then again every time `path` is saved.
+// Only returns if the file can't be watched.
+pub fn watch(path: &Path, mut render: impl FnMut()) -> Result<(), CheatError> {
+    let watch_error = |source| CheatError::Watch {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    // Watching the directory rather than the file itself survives editors
+    // that save by replacing the file
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+
+    loop {
+        if io::stdout().is_terminal() {
+            let _ = execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
+        }
+        render();
+        let _ = io::stdout().flush();
+
+        loop {
+            let event = receiver
+                .recv()
+                .map_err(|_| watch_error(notify::Error::generic("watcher stopped")))?
+                .map_err(watch_error)?;
+            if is_save_of(&event, path) {
+                break;
+            }
+        }
+        while receiver.recv_timeout(SETTLE_TIME).is_ok() {}
+    }
+}
+
+fn is_save_of(event: &Event, path: &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name())
+}